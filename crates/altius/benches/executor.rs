@@ -0,0 +1,134 @@
+//! Benchmark comparing `AltiusExecutor` with the stock reth executor on the same blocks.
+//!
+//! Each fixture is a hex-encoded RLP block checked in under `testdata/bench`, and both executors
+//! run it from a fresh copy of its pre-state. Criterion reports the two executors side by side in
+//! the fixture's group, and `--save-baseline` / `--baseline` can be used to guard against
+//! regressions. After each group, the mean reth time divided by the mean Altius time is printed
+//! as the fixture's speedup.
+//!
+//! The pre-state is derived from the block, so the fixtures run without a database: every sender
+//! is funded and the contracts the blocks call are deployed. `independent_transfers` has no
+//! conflicts between transactions and is the best case for parallel execution, while
+//! `single_sender` and `shared_contract` chain every transaction to the previous one and are
+//! serial by nature. `disjoint_slots` has every transaction write its own slot of the same
+//! contract, so it only parallelizes if conflicts are tracked per slot.
+//!
+//! Every block holds 200 legacy mainnet transactions sending 1 wei with a gas price of 1 and a
+//! gas limit of 100000, signed with the private keys 1 to 200. `single_sender` signs all of them
+//! with key 1.
+
+#![allow(missing_docs)]
+
+use alloy_primitives::{bytes, hex, Address, U256};
+use criterion::{criterion_group, criterion_main, Bencher, Criterion};
+use reth_chainspec::{ChainSpec, ChainSpecBuilder, MAINNET};
+use reth_ethereum_primitives::Block;
+use reth_evm::execute::{BlockExecutorProvider, Executor};
+use reth_evm_altius::{config::AltiusEvmConfig, AltiusBlockExecutorProvider};
+use reth_evm_ethereum::execute::EthExecutorProvider;
+use reth_primitives_traits::{Block as _, RecoveredBlock};
+use revm::{
+    database::{CacheDB, EmptyDB},
+    state::{AccountInfo, Bytecode},
+};
+use std::{
+    hint::black_box,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// The fixture blocks, as hex-encoded RLP.
+const FIXTURES: [(&str, &str); 4] = [
+    ("independent_transfers", include_str!("../testdata/bench/independent_transfers.rlp")),
+    ("single_sender", include_str!("../testdata/bench/single_sender.rlp")),
+    ("shared_contract", include_str!("../testdata/bench/shared_contract.rlp")),
+    ("disjoint_slots", include_str!("../testdata/bench/disjoint_slots.rlp")),
+];
+
+/// A contract flipping storage slot 0 on every call: `SSTORE(0, ISZERO(SLOAD(0)))`.
+const TOGGLE: Address = Address::with_last_byte(0xaa);
+
+/// A contract writing to the slot of its caller: `SSTORE(CALLER, 1)`.
+const REGISTRY: Address = Address::with_last_byte(0xbb);
+
+struct Fixture {
+    name: &'static str,
+    db: CacheDB<EmptyDB>,
+    block: RecoveredBlock<Block>,
+}
+
+fn chain_spec() -> Arc<ChainSpec> {
+    Arc::new(ChainSpecBuilder::from(&*MAINNET).shanghai_activated().build())
+}
+
+/// Decodes a fixture block and builds its pre-state.
+fn fixture(name: &'static str, rlp: &str) -> Fixture {
+    let rlp = hex::decode(rlp.trim()).unwrap();
+    let block: Block = alloy_rlp::decode_exact(&rlp).unwrap();
+    let block = block.try_into_recovered().unwrap();
+
+    let mut db = CacheDB::new(EmptyDB::default());
+    for (address, code) in
+        [(TOGGLE, bytes!("0x6000541560005500")), (REGISTRY, bytes!("0x336001905500"))]
+    {
+        let code = Some(Bytecode::new_raw(code));
+        db.insert_account_info(address, AccountInfo { code, ..Default::default() });
+    }
+    for sender in block.senders() {
+        db.insert_account_info(
+            *sender,
+            AccountInfo { balance: U256::from(u64::MAX), ..Default::default() },
+        );
+    }
+
+    Fixture { name, db, block }
+}
+
+/// Times one block execution per iteration, leaving out the executor setup, and records the
+/// mean time of the last sample in `mean`.
+fn measure(b: &mut Bencher<'_>, mean: &mut Duration, mut execute: impl FnMut() -> Duration) {
+    b.iter_custom(|iters| {
+        let elapsed = (0..iters).map(|_| execute()).sum::<Duration>();
+        *mean = elapsed.div_f64(iters as f64);
+        elapsed
+    });
+}
+
+fn bench_executors(c: &mut Criterion) {
+    let chain_spec = chain_spec();
+    let altius = AltiusBlockExecutorProvider::new(AltiusEvmConfig::new(chain_spec.clone()));
+    let reth = EthExecutorProvider::ethereum(chain_spec);
+
+    for Fixture { name, db, block } in FIXTURES.map(|(name, rlp)| fixture(name, rlp)) {
+        let mut group = c.benchmark_group(name);
+        let (mut altius_mean, mut reth_mean) = (Duration::ZERO, Duration::ZERO);
+
+        group.bench_function("altius", |b| {
+            measure(b, &mut altius_mean, || {
+                let mut executor = altius.executor(db.clone());
+                let start = Instant::now();
+                black_box(executor.execute_one(&block).unwrap());
+                start.elapsed()
+            })
+        });
+        group.bench_function("reth", |b| {
+            measure(b, &mut reth_mean, || {
+                let mut executor = reth.executor(db.clone());
+                let start = Instant::now();
+                black_box(executor.execute_one(&block).unwrap());
+                start.elapsed()
+            })
+        });
+
+        group.finish();
+
+        // Both means are zero when the group was filtered out
+        if !altius_mean.is_zero() && !reth_mean.is_zero() {
+            let speedup = reth_mean.as_secs_f64() / altius_mean.as_secs_f64();
+            println!("{name}: altius speedup over reth {speedup:.2}x");
+        }
+    }
+}
+
+criterion_group!(executors, bench_executors);
+criterion_main!(executors);
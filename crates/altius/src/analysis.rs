@@ -0,0 +1,200 @@
+//! Conflict analysis of transactions without executing them.
+//!
+//! [`estimate_parallelism`] predicts how well a list of transactions parallelizes from what
+//! each of them is expected to touch. It is much cheaper than executing the block and lets
+//! builders compare transaction orderings before committing to one.
+
+use alloy_consensus::Transaction;
+use alloy_eips::eip2930::AccessList;
+use alloy_primitives::{Address, B256};
+use reth_primitives_traits::Recovered;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The predicted parallelism of a list of transactions, computed by [`estimate_parallelism`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParallelismEstimate {
+    /// Number of transactions analyzed.
+    pub transactions: usize,
+    /// Pairs of transaction indices `(earlier, later)` where the later transaction touches
+    /// something the earlier one writes, sorted.
+    pub conflicts: Vec<(usize, usize)>,
+    /// Number of transactions in the longest chain of conflicting transactions, i.e. the
+    /// number of rounds needed even with unlimited workers.
+    pub critical_path: usize,
+    /// Largest number of transactions that can run in the same round.
+    pub max_width: usize,
+}
+
+/// Something a transaction reads or writes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum AccessKey {
+    /// The nonce, balance and code of an account.
+    Account(Address),
+    /// A storage slot of an account.
+    Slot(Address, B256),
+}
+
+/// Estimates how well `txs` parallelize, without running the EVM.
+///
+/// Every transaction writes its sender's account, and the recipient's account when it
+/// transfers value. Its other accesses come from `touched_slots_hint`, one access list per
+/// transaction, falling back to the transaction's own EIP-2930 access list when no hint is
+/// given. Access lists do not tell reads from writes, so every listed slot is assumed to be
+/// written and listed accounts without slots to be read. The estimate is therefore
+/// conservative: transactions that only read the same slot are reported as conflicting.
+/// Accesses missing from the hints, such as storage touched by nested calls, are not seen.
+///
+/// A later transaction conflicts with an earlier one when it reads or writes something the
+/// earlier one writes. Transactions are then grouped in rounds, each transaction running in
+/// the round after the last of the transactions it conflicts with.
+///
+/// # Parameters
+///
+/// * `txs` - The transactions, in block order
+/// * `touched_slots_hint` - The expected accesses of each transaction, by index
+///
+/// # Returns
+///
+/// The conflicts between the transactions, their critical path and the maximum width
+pub fn estimate_parallelism<T: Transaction>(
+    txs: &[Recovered<T>],
+    touched_slots_hint: &[AccessList],
+) -> ParallelismEstimate {
+    // Indices of all earlier transactions that wrote each key
+    let mut writers = BTreeMap::<AccessKey, Vec<usize>>::new();
+    let mut rounds = Vec::with_capacity(txs.len());
+    let mut conflicts = BTreeSet::new();
+
+    for (index, tx) in txs.iter().enumerate() {
+        let mut reads = BTreeSet::new();
+        let mut writes = BTreeSet::from([AccessKey::Account(tx.signer())]);
+        if let Some(to) = tx.to() {
+            if tx.value().is_zero() {
+                reads.insert(AccessKey::Account(to));
+            } else {
+                writes.insert(AccessKey::Account(to));
+            }
+        }
+
+        let access_list = touched_slots_hint.get(index).or_else(|| tx.access_list());
+        for item in access_list.iter().flat_map(|list| list.iter()) {
+            if item.storage_keys.is_empty() {
+                reads.insert(AccessKey::Account(item.address));
+            }
+            writes
+                .extend(item.storage_keys.iter().map(|slot| AccessKey::Slot(item.address, *slot)));
+        }
+
+        let mut round = 0;
+        for key in reads.iter().chain(&writes) {
+            for &earlier in writers.get(key).into_iter().flatten() {
+                conflicts.insert((earlier, index));
+                round = round.max(rounds[earlier] + 1);
+            }
+        }
+        rounds.push(round);
+
+        for key in writes {
+            writers.entry(key).or_default().push(index);
+        }
+    }
+
+    let mut widths = BTreeMap::<usize, usize>::new();
+    for round in &rounds {
+        *widths.entry(*round).or_default() += 1;
+    }
+
+    ParallelismEstimate {
+        transactions: txs.len(),
+        conflicts: conflicts.into_iter().collect(),
+        critical_path: widths.len(),
+        max_width: widths.values().copied().max().unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_consensus::TxLegacy;
+    use alloy_eips::eip2930::AccessListItem;
+    use alloy_primitives::{TxKind, U256};
+    use reth_ethereum_primitives::{Transaction as EthTransaction, TransactionSigned};
+    use reth_testing_utils::generators::{self, sign_tx_with_key_pair};
+
+    const CONTRACT: Address = Address::with_last_byte(0xcc);
+
+    /// Returns a transaction from `sender` to `to` transferring `value`.
+    fn tx(sender: u8, to: Address, value: u64) -> Recovered<TransactionSigned> {
+        let key_pair = generators::generate_key(&mut generators::rng());
+        let tx = sign_tx_with_key_pair(
+            key_pair,
+            EthTransaction::Legacy(TxLegacy {
+                to: TxKind::Call(to),
+                value: U256::from(value),
+                ..Default::default()
+            }),
+        );
+        // The signer is not checked, which lets the test choose the senders
+        Recovered::new_unchecked(tx, Address::with_last_byte(sender))
+    }
+
+    fn slot_hint(slot: u8) -> AccessList {
+        AccessList(vec![AccessListItem {
+            address: CONTRACT,
+            storage_keys: vec![B256::with_last_byte(slot)],
+        }])
+    }
+
+    #[test]
+    fn estimate_of_known_overlaps() {
+        let recipient = |byte| Address::with_last_byte(0x80 + byte);
+        let txs = [
+            tx(1, recipient(1), 1),
+            // Same sender as 0
+            tx(1, recipient(2), 1),
+            tx(2, recipient(3), 1),
+            tx(3, CONTRACT, 0),
+            // Same slot as 3
+            tx(4, CONTRACT, 0),
+            // Another slot of the same contract
+            tx(5, CONTRACT, 0),
+            // Pays the recipient of 0
+            tx(6, recipient(1), 1),
+        ];
+        let hints = [
+            AccessList::default(),
+            AccessList::default(),
+            AccessList::default(),
+            slot_hint(1),
+            slot_hint(1),
+            slot_hint(2),
+        ];
+
+        let estimate = estimate_parallelism(&txs, &hints);
+
+        assert_eq!(
+            estimate,
+            ParallelismEstimate {
+                transactions: 7,
+                conflicts: vec![(0, 1), (0, 6), (3, 4)],
+                critical_path: 2,
+                max_width: 4,
+            }
+        );
+    }
+
+    #[test]
+    fn chain_of_one_sender_is_serial() {
+        let txs: Vec<_> =
+            (0..5).map(|byte| tx(1, Address::with_last_byte(0x80 + byte), 1)).collect();
+
+        let estimate = estimate_parallelism(&txs, &[]);
+
+        assert_eq!(estimate.critical_path, 5);
+        assert_eq!(estimate.max_width, 1);
+        assert_eq!(
+            estimate_parallelism::<TransactionSigned>(&[], &[]),
+            ParallelismEstimate::default()
+        );
+    }
+}
@@ -1,15 +1,16 @@
 extern crate alloc;
 
 use alloc::{borrow::Cow, sync::Arc};
-use alloy_consensus::{BlockHeader, Header};
+use alloy_consensus::{constants::MAXIMUM_EXTRA_DATA_SIZE, BlockHeader, Header};
 pub use alloy_evm::EthEvm;
 use alloy_evm::{
-    eth::EthBlockExecutionCtx, FromRecoveredTx, FromTxWithEncoded, IntoTxEnv,
+    eth::{receipt_builder::ReceiptBuilder, EthBlockExecutionCtx},
+    FromRecoveredTx, FromTxWithEncoded, IntoTxEnv,
 };
-use alloy_primitives::{Bytes, U256};
+use alloy_primitives::{Address, Bytes, U256};
 use core::{convert::Infallible, fmt::Debug};
-use reth_chainspec::{ChainSpec, EthChainSpec, MAINNET};
-use reth_ethereum_primitives::{Block, EthPrimitives, TransactionSigned};
+use reth_chainspec::{ChainSpec, EthChainSpec, NamedChain, DEV, HOLESKY, HOODI, MAINNET, SEPOLIA};
+use reth_ethereum_primitives::{Block, EthPrimitives, Receipt, TransactionSigned};
 use reth_evm::{ConfigureEvm, EvmEnv, EvmFactory, NextBlockEnvAttributes, TransactionEnv};
 use reth_primitives_traits::{SealedBlock, SealedHeader};
 use revm::{
@@ -23,6 +24,17 @@ use reth_ethereum::evm::{RethReceiptBuilder, EthBlockAssembler, revm_spec_by_tim
 use alloy_altius_evm::block::{AltiusBlockExecutorFactory, AltiusEvmFactory, EnvProvider};
 use revm::context_interface::result::HaltReason;
 
+/// Error returned when block extra data is longer than the protocol allows.
+/// 
+/// Ethereum limits header extra data to [`MAXIMUM_EXTRA_DATA_SIZE`] bytes; an oversized value
+/// would otherwise only surface when an assembled block fails validation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("extra data is {len} bytes, exceeding the {MAXIMUM_EXTRA_DATA_SIZE}-byte limit")]
+pub struct ExtraDataTooLong {
+    /// Length of the rejected extra data.
+    pub len: usize,
+}
+
 /// Configuration for the Altius Ethereum Virtual Machine (EVM).
 /// 
 /// This struct encapsulates the necessary components for configuring and running
@@ -47,16 +59,48 @@ use revm::context_interface::result::HaltReason;
 /// let config = AltiusEvmConfig::new(MAINNET.clone());
 /// ```
 #[derive(Debug, Clone)]
-pub struct AltiusEvmConfig<EvmFactory = AltiusEvmFactory> {
+pub struct AltiusEvmConfig<EvmFactory = AltiusEvmFactory, R = RethReceiptBuilder> {
     /// The block executor factory responsible for creating block executors.
     /// This factory handles the creation of executors that can process blocks
     /// using the configured EVM and receipt builder.
-    pub executor_factory: AltiusBlockExecutorFactory<RethReceiptBuilder, Arc<ChainSpec>, EvmFactory>,
+    pub executor_factory: AltiusBlockExecutorFactory<R, Arc<ChainSpec>, EvmFactory>,
     
     /// The Ethereum block assembler used for constructing new blocks.
     /// This component handles the assembly of transactions into blocks
     /// according to Ethereum protocol rules.
     pub block_assembler: EthBlockAssembler<ChainSpec>,
+
+    /// Hardfork specification forced onto every produced environment.
+    /// When `None`, the spec is derived from the chain specification and the block.
+    pub spec_override: Option<SpecId>,
+
+    /// Chain id written into the `CfgEnv` instead of the chain specification's id.
+    /// Only affects transaction signature checks, never fork or reward logic.
+    pub chain_id_override: Option<u64>,
+
+    /// Whether the EIP-1559 base fee check is skipped. Never enabled for consensus execution.
+    pub disable_base_fee: bool,
+
+    /// Whether the sender balance check is skipped. Never enabled for consensus execution.
+    pub disable_balance_check: bool,
+
+    /// Upper bound applied to the gas limit computed by `next_evm_env`.
+    pub gas_limit_cap: Option<u64>,
+
+    /// EIP-4844 blob parameters used instead of the chain specification's schedule.
+    pub blob_params_override: Option<BlobParams>,
+
+    /// Ommers included in blocks built by `context_for_next_block`.
+    /// Empty by default, which is the only valid value after the merge.
+    pub next_block_ommers: Vec<Header>,
+
+    /// Beneficiary written into the `BlockEnv` instead of the header's or the suggested one.
+    /// Only meant for simulation, e.g. measuring builder profit with a substituted coinbase.
+    pub beneficiary_override: Option<Address>,
+
+    /// Maximum number of transactions of a block executors built from this configuration
+    /// accept. Carried over by `AltiusBlockExecutorProvider::from_config`.
+    pub max_transactions: Option<usize>,
 }
 
 impl AltiusEvmConfig {
@@ -102,6 +146,49 @@ impl AltiusEvmConfig {
     pub fn mainnet() -> Self {
         Self::ethereum(MAINNET.clone())
     }
+
+    /// Creates a new Ethereum EVM configuration specifically for the Sepolia testnet.
+    /// 
+    /// # Returns
+    /// 
+    /// An `AltiusEvmConfig` instance configured for Sepolia
+    pub fn sepolia() -> Self {
+        Self::ethereum(SEPOLIA.clone())
+    }
+
+    /// Creates a new Ethereum EVM configuration specifically for the Holesky testnet.
+    /// 
+    /// # Returns
+    /// 
+    /// An `AltiusEvmConfig` instance configured for Holesky
+    pub fn holesky() -> Self {
+        Self::ethereum(HOLESKY.clone())
+    }
+
+    /// Creates a new Ethereum EVM configuration for a well-known network.
+    /// 
+    /// This resolves the predefined chain specification for the given network so tests and
+    /// tooling don't have to import the chain specification constants themselves.
+    /// 
+    /// # Parameters
+    /// 
+    /// * `chain` - The network to configure
+    /// 
+    /// # Returns
+    /// 
+    /// An `AltiusEvmConfig` for the network, or `None` if no chain specification is bundled
+    /// for it (supported: mainnet, Sepolia, Holesky, Hoodi and dev)
+    pub fn from_named_chain(chain: NamedChain) -> Option<Self> {
+        let chain_spec = match chain {
+            NamedChain::Mainnet => MAINNET.clone(),
+            NamedChain::Sepolia => SEPOLIA.clone(),
+            NamedChain::Holesky => HOLESKY.clone(),
+            NamedChain::Hoodi => HOODI.clone(),
+            NamedChain::Dev => DEV.clone(),
+            _ => return None,
+        };
+        Some(Self::ethereum(chain_spec))
+    }
 }
 
 impl<EvmFactory> AltiusEvmConfig<EvmFactory>
@@ -120,13 +207,46 @@ impl<EvmFactory> AltiusEvmConfig<EvmFactory>
     /// 
     /// A new `AltiusEvmConfig` configured with the provided factory
     pub fn new_with_evm_factory(chain_spec: Arc<ChainSpec>, evm_factory: EvmFactory) -> Self {
+        Self::new_with_components(chain_spec, evm_factory, RethReceiptBuilder::default())
+    }
+}
+
+impl<EvmFactory, R> AltiusEvmConfig<EvmFactory, R> {
+    /// Creates a new Altius EVM configuration from all of its components.
+    /// 
+    /// This is the most general constructor, additionally accepting the receipt builder
+    /// used to turn transaction results into receipts.
+    /// 
+    /// # Parameters
+    /// 
+    /// * `chain_spec` - The blockchain specification
+    /// * `evm_factory` - The EVM factory instance
+    /// * `receipt_builder` - The receipt builder instance
+    /// 
+    /// # Returns
+    /// 
+    /// A new `AltiusEvmConfig` configured with the provided components
+    pub fn new_with_components(
+        chain_spec: Arc<ChainSpec>,
+        evm_factory: EvmFactory,
+        receipt_builder: R,
+    ) -> Self {
         Self {
             block_assembler: EthBlockAssembler::new(chain_spec.clone()),
             executor_factory: AltiusBlockExecutorFactory::new(
-                RethReceiptBuilder::default(),
+                receipt_builder,
                 chain_spec,
                 evm_factory,
             ),
+            spec_override: None,
+            chain_id_override: None,
+            disable_base_fee: false,
+            disable_balance_check: false,
+            gas_limit_cap: None,
+            blob_params_override: None,
+            next_block_ommers: Vec::new(),
+            beneficiary_override: None,
+            max_transactions: None,
         }
     }
 
@@ -158,9 +278,335 @@ impl<EvmFactory> AltiusEvmConfig<EvmFactory>
         self.block_assembler.extra_data = extra_data;
         self
     }
+
+    /// Sets the extra data for block assembly, rejecting values longer than
+    /// [`MAXIMUM_EXTRA_DATA_SIZE`] bytes.
+    /// 
+    /// Prefer this over [`Self::with_extra_data`] when the value comes from user input, so an
+    /// invalid value is reported up front instead of during block assembly.
+    /// 
+    /// # Parameters
+    /// 
+    /// * `extra_data` - The extra data bytes to include in assembled blocks
+    /// 
+    /// # Returns
+    /// 
+    /// A modified configuration, or an [`ExtraDataTooLong`] error
+    pub fn try_with_extra_data(self, extra_data: Bytes) -> Result<Self, ExtraDataTooLong> {
+        if extra_data.len() > MAXIMUM_EXTRA_DATA_SIZE {
+            return Err(ExtraDataTooLong { len: extra_data.len() });
+        }
+        Ok(self.with_extra_data(extra_data))
+    }
+
+    /// Replaces the receipt builder used by the block executor factory.
+    /// 
+    /// This allows custom receipt post-processing, e.g. attaching execution metadata,
+    /// while keeping every other part of the configuration.
+    /// 
+    /// # Parameters
+    /// 
+    /// * `receipt_builder` - The receipt builder to use for executed transactions
+    /// 
+    /// # Returns
+    /// 
+    /// A configuration using the given receipt builder
+    pub fn with_receipt_builder<B>(self, receipt_builder: B) -> AltiusEvmConfig<EvmFactory, B>
+    where
+        EvmFactory: Clone,
+    {
+        let Self {
+            executor_factory,
+            block_assembler,
+            spec_override,
+            chain_id_override,
+            disable_base_fee,
+            disable_balance_check,
+            gas_limit_cap,
+            blob_params_override,
+            next_block_ommers,
+            beneficiary_override,
+            max_transactions,
+        } = self;
+
+        AltiusEvmConfig {
+            executor_factory: AltiusBlockExecutorFactory::new(
+                receipt_builder,
+                executor_factory.spec().clone(),
+                executor_factory.evm_factory().clone(),
+            ),
+            block_assembler,
+            spec_override,
+            chain_id_override,
+            disable_base_fee,
+            disable_balance_check,
+            gas_limit_cap,
+            blob_params_override,
+            next_block_ommers,
+            beneficiary_override,
+            max_transactions,
+        }
+    }
+
+    /// Forces the hardfork specification used for execution.
+    /// 
+    /// When set, `evm_env` and `next_evm_env` use this `SpecId` regardless of the block
+    /// number and timestamp. This is useful for testing EIP behavior or replaying blocks on
+    /// a forked specification. Passing `None` restores the chain specification's schedule.
+    /// 
+    /// # Parameters
+    /// 
+    /// * `spec` - The specification to use for every block, if any
+    /// 
+    /// # Returns
+    /// 
+    /// A modified configuration with the specified override
+    pub fn with_spec_override(mut self, spec: Option<SpecId>) -> Self {
+        self.spec_override = spec;
+        self
+    }
+
+    /// Overrides the chain id used by the EVM.
+    /// 
+    /// This allows executing replayed data from one network while enforcing a different
+    /// chain id for replay protection, e.g. when testing signatures on a private chain.
+    /// The chain specification itself is left untouched, so hardfork activation and block
+    /// rewards still follow the original network.
+    /// 
+    /// # Parameters
+    /// 
+    /// * `chain_id` - The chain id to put into the `CfgEnv`, if any
+    /// 
+    /// # Returns
+    /// 
+    /// A modified configuration with the specified override
+    pub fn with_chain_id_override(mut self, chain_id: Option<u64>) -> Self {
+        self.chain_id_override = chain_id;
+        self
+    }
+
+    /// Disables the base fee check for executed transactions.
+    /// 
+    /// Intended for trace replay and `eth_call`-style execution where transactions may not
+    /// pay the block's base fee.
+    /// 
+    /// **This must never be enabled for consensus execution**, as it accepts transactions
+    /// that are invalid on chain.
+    /// 
+    /// # Parameters
+    /// 
+    /// * `disable` - Whether to skip the base fee check
+    /// 
+    /// # Returns
+    /// 
+    /// A modified configuration with the flag applied to every produced `CfgEnv`
+    pub fn with_disable_base_fee(mut self, disable: bool) -> Self {
+        self.disable_base_fee = disable;
+        self
+    }
+
+    /// Disables the sender balance check for executed transactions.
+    /// 
+    /// Intended for trace replay and `eth_call`-style execution from accounts that cannot
+    /// afford the transaction.
+    /// 
+    /// **This must never be enabled for consensus execution**, as it accepts transactions
+    /// that are invalid on chain.
+    /// 
+    /// # Parameters
+    /// 
+    /// * `disable` - Whether to skip the balance check
+    /// 
+    /// # Returns
+    /// 
+    /// A modified configuration with the flag applied to every produced `CfgEnv`
+    pub fn with_disable_balance_check(mut self, disable: bool) -> Self {
+        self.disable_balance_check = disable;
+        self
+    }
+
+    /// Caps the gas limit of blocks built on top of this configuration.
+    /// 
+    /// The cap is applied in `next_evm_env` after the London elasticity adjustment and can
+    /// only lower the gas limit, never raise it. The base fee is unaffected. Block builders
+    /// can use this to keep blocks smaller than the protocol allows for latency reasons.
+    /// 
+    /// # Parameters
+    /// 
+    /// * `cap` - The maximum gas limit for the next block, if any
+    /// 
+    /// # Returns
+    /// 
+    /// A modified configuration with the specified cap
+    pub fn with_gas_limit_cap(mut self, cap: Option<u64>) -> Self {
+        self.gas_limit_cap = cap;
+        self
+    }
+
+    /// Limits the number of transactions of the blocks executors built from this
+    /// configuration accept.
+    /// 
+    /// A block with more transactions fails with
+    /// [`PrecheckError::ExceedsConfiguredLimit`](crate::validate::PrecheckError::ExceedsConfiguredLimit)
+    /// before any EVM work is done, leaving the executor's state untouched. This protects
+    /// executors exposed to untrusted blocks, e.g. simulation requests, from resource
+    /// exhaustion. Unlike the gas-based check of
+    /// [`precheck_block`](crate::validate::precheck_block), the limit does not depend on the
+    /// block's header.
+    /// 
+    /// The limit is applied to executors created by
+    /// [`AltiusBlockExecutorProvider::from_config`](crate::AltiusBlockExecutorProvider::from_config).
+    /// Providers created with `AltiusBlockExecutorProvider::new` take their limit from their
+    /// own `with_max_transactions` instead.
+    /// 
+    /// # Parameters
+    /// 
+    /// * `limit` - The maximum number of transactions per block, or `None` for no limit
+    /// 
+    /// # Returns
+    /// 
+    /// A modified configuration with the specified limit
+    pub fn with_max_transactions(mut self, limit: Option<usize>) -> Self {
+        self.max_transactions = limit;
+        self
+    }
+
+    /// Overrides the EIP-4844 blob parameters.
+    /// 
+    /// When set, these parameters replace the chain specification's blob schedule in both
+    /// `evm_env` and `next_evm_env`. Only the parameters change: the excess blob gas is still
+    /// taken from the header. This lets researchers evaluate alternative blob pricing
+    /// against real blocks.
+    /// 
+    /// # Parameters
+    /// 
+    /// * `params` - The blob parameters to use, if any
+    /// 
+    /// # Returns
+    /// 
+    /// A modified configuration with the specified override
+    pub fn with_blob_params_override(mut self, params: Option<BlobParams>) -> Self {
+        self.blob_params_override = params;
+        self
+    }
+
+    /// Sets the ommers (uncle headers) used when building the next block.
+    /// 
+    /// Post-merge blocks never contain ommers, so this defaults to an empty list. Chains that
+    /// still include uncles, or tooling building pre-merge blocks, can supply them here and
+    /// they will be passed to the execution context created by `context_for_next_block`.
+    /// 
+    /// # Parameters
+    /// 
+    /// * `ommers` - The ommer headers for the next block
+    /// 
+    /// # Returns
+    /// 
+    /// A modified configuration with the specified ommers
+    pub fn with_next_block_ommers(mut self, ommers: Vec<Header>) -> Self {
+        self.next_block_ommers = ommers;
+        self
+    }
+
+    /// Overrides the beneficiary (coinbase) of executed and built blocks.
+    /// 
+    /// When set, `evm_env` uses this address instead of the header's beneficiary and
+    /// `next_evm_env` uses it instead of the suggested fee recipient, so priority fees and
+    /// block rewards are credited to it. MEV simulators use this to measure builder profit.
+    /// 
+    /// **This must never be set for consensus execution**, as it changes the resulting state.
+    /// With `None` (the default) the environments are built exactly as without an override.
+    /// 
+    /// # Parameters
+    /// 
+    /// * `beneficiary` - The beneficiary to use for every block, if any
+    /// 
+    /// # Returns
+    /// 
+    /// A modified configuration with the specified override
+    pub fn with_beneficiary_override(mut self, beneficiary: Option<Address>) -> Self {
+        self.beneficiary_override = beneficiary;
+        self
+    }
+
+    /// Returns the EVM specification a block with the given header executes under.
+    /// 
+    /// This is the specification used by `evm_env`: the spec override if one is set,
+    /// otherwise the hardfork active at the header's block number and timestamp according to
+    /// the chain specification.
+    /// 
+    /// # Parameters
+    /// 
+    /// * `header` - The header of the block
+    /// 
+    /// # Returns
+    /// 
+    /// The `SpecId` used to execute the block
+    pub fn spec_for_header(&self, header: &Header) -> SpecId {
+        self.spec_override.unwrap_or_else(|| revm_spec(self.chain_spec(), header))
+    }
+
+    /// Returns the EVM specification the next block on top of `parent` executes under.
+    /// 
+    /// This is the specification used by `next_evm_env`, resolved from the next block number
+    /// and the timestamp in `attributes`, so timestamp-based hardforks activating with the next
+    /// block are taken into account.
+    /// 
+    /// # Parameters
+    /// 
+    /// * `parent` - The header of the parent block
+    /// * `attributes` - The attributes of the next block
+    /// 
+    /// # Returns
+    /// 
+    /// The `SpecId` used to execute the next block
+    pub fn spec_for_next_block(
+        &self,
+        parent: &Header,
+        attributes: &NextBlockEnvAttributes,
+    ) -> SpecId {
+        self.spec_override.unwrap_or_else(|| {
+            revm_spec_by_timestamp_and_block_number(
+                self.chain_spec(),
+                attributes.timestamp,
+                parent.number() + 1,
+            )
+        })
+    }
+
+    /// Returns the `CfgEnv` a block with the given header executes under.
+    /// 
+    /// This is exactly the `cfg_env` of the environment built by `evm_env`, with the chain id,
+    /// the specification and the validation flags resolved from the chain specification and
+    /// the configured overrides. Use it to mirror the configuration in an external simulator.
+    /// 
+    /// # Parameters
+    /// 
+    /// * `header` - The header of the block
+    /// 
+    /// # Returns
+    /// 
+    /// The `CfgEnv` used to execute the block
+    pub fn cfg_env_for_header(&self, header: &Header) -> CfgEnv {
+        self.cfg_env(self.spec_for_header(header))
+    }
+
+    /// Returns the blob parameters active at the given timestamp, honoring the override.
+    fn blob_params_at_timestamp(&self, timestamp: u64) -> Option<BlobParams> {
+        self.blob_params_override.or_else(|| self.chain_spec().blob_params_at_timestamp(timestamp))
+    }
+
+    /// Builds the `CfgEnv` for the given spec, applying the configured overrides.
+    fn cfg_env(&self, spec: SpecId) -> CfgEnv {
+        let chain_id = self.chain_id_override.unwrap_or_else(|| self.chain_spec().chain().id());
+        let mut cfg_env = CfgEnv::new().with_chain_id(chain_id).with_spec(spec);
+        cfg_env.disable_base_fee = self.disable_base_fee;
+        cfg_env.disable_balance_check = self.disable_balance_check;
+        cfg_env
+    }
 }
 
-impl<EvmF> ConfigureEvm for AltiusEvmConfig<EvmF>
+impl<EvmF, R> ConfigureEvm for AltiusEvmConfig<EvmF, R>
 where
     EvmF: EvmFactory<
             Tx: TransactionEnv
@@ -176,11 +622,18 @@ where
         + Unpin
         + EnvProvider
         + 'static,
+    R: ReceiptBuilder<Transaction = TransactionSigned, Receipt = Receipt>
+        + Clone
+        + Debug
+        + Send
+        + Sync
+        + Unpin
+        + 'static,
 {
     type Primitives = EthPrimitives;
     type Error = Infallible;
     type NextBlockEnvCtx = NextBlockEnvAttributes;
-    type BlockExecutorFactory = AltiusBlockExecutorFactory<RethReceiptBuilder, Arc<ChainSpec>, EvmF>;
+    type BlockExecutorFactory = AltiusBlockExecutorFactory<R, Arc<ChainSpec>, EvmF>;
     type BlockAssembler = EthBlockAssembler<ChainSpec>;
 
     /// Returns a reference to the block executor factory.
@@ -213,16 +666,15 @@ where
     /// 
     /// An `EvmEnv` configured for executing transactions in the specified block
     fn evm_env(&self, header: &Header) -> EvmEnv {
-        let spec = revm_spec(self.chain_spec(), header);
-
         // Configure EVM environment based on parent block
-        let cfg_env = CfgEnv::new().with_chain_id(self.chain_spec().chain().id()).with_spec(spec);
+        let cfg_env = self.cfg_env_for_header(header);
+        let spec = cfg_env.spec;
 
         // Derive the EIP-4844 blob fees from the header's `excess_blob_gas` and the current
         // blob parameters for dynamic blob pricing
         let blob_excess_gas_and_price = header
             .excess_blob_gas
-            .zip(self.chain_spec().blob_params_at_timestamp(header.timestamp))
+            .zip(self.blob_params_at_timestamp(header.timestamp))
             .map(|(excess_blob_gas, params)| {
                 let blob_gasprice = params.calc_blob_fee(excess_blob_gas);
                 BlobExcessGasAndPrice { excess_blob_gas, blob_gasprice }
@@ -230,7 +682,7 @@ where
 
         let block_env = BlockEnv {
             number: header.number(),
-            beneficiary: header.beneficiary(),
+            beneficiary: self.beneficiary_override.unwrap_or_else(|| header.beneficiary()),
             timestamp: header.timestamp(),
             difficulty: if spec >= SpecId::MERGE { U256::ZERO } else { header.difficulty() },
             prevrandao: if spec >= SpecId::MERGE { header.mix_hash() } else { None },
@@ -262,16 +714,12 @@ where
         attributes: &NextBlockEnvAttributes,
     ) -> Result<EvmEnv, Self::Error> {
         // Ensure we're not missing any timestamp-based hard forks
-        let spec_id = revm_spec_by_timestamp_and_block_number(
-            self.chain_spec(),
-            attributes.timestamp,
-            parent.number() + 1,
-        );
+        let spec_id = self.spec_for_next_block(parent, attributes);
 
         // Configure EVM environment based on parent block
-        let cfg = CfgEnv::new().with_chain_id(self.chain_spec().chain().id()).with_spec(spec_id);
+        let cfg = self.cfg_env(spec_id);
 
-        let blob_params = self.chain_spec().blob_params_at_timestamp(attributes.timestamp);
+        let blob_params = self.blob_params_at_timestamp(attributes.timestamp);
         // If the parent block did not have excess blob gas (i.e., it was pre-Cancun), but it is
         // Cancun now, we need to set the excess blob gas to the default value (0)
         let blob_excess_gas_and_price = parent
@@ -305,9 +753,14 @@ where
             basefee = Some(INITIAL_BASE_FEE)
         }
 
+        // Apply the configured cap last so it also bounds the London boundary adjustment
+        if let Some(cap) = self.gas_limit_cap {
+            gas_limit = gas_limit.min(cap);
+        }
+
         let block_env = BlockEnv {
             number: parent.number + 1,
-            beneficiary: attributes.suggested_fee_recipient,
+            beneficiary: self.beneficiary_override.unwrap_or(attributes.suggested_fee_recipient),
             timestamp: attributes.timestamp,
             difficulty: U256::ZERO,
             prevrandao: Some(attributes.prev_randao),
@@ -365,8 +818,273 @@ where
         EthBlockExecutionCtx {
             parent_hash: parent.hash(),
             parent_beacon_block_root: attributes.parent_beacon_block_root,
-            ommers: &[],
+            ommers: &self.next_block_ommers,
             withdrawals: attributes.withdrawals.map(Cow::Owned),
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{address, B256};
+    use reth_chainspec::{ChainSpecBuilder, ForkCondition};
+
+    fn next_block_attributes(parent: &Header) -> NextBlockEnvAttributes {
+        NextBlockEnvAttributes {
+            timestamp: parent.timestamp + 12,
+            suggested_fee_recipient: Address::ZERO,
+            prev_randao: B256::ZERO,
+            gas_limit: parent.gas_limit,
+            parent_beacon_block_root: None,
+            withdrawals: None,
+        }
+    }
+
+    #[test]
+    fn spec_override_applies_to_pre_shanghai_header() {
+        let header = Header { number: 1, timestamp: 1, ..Header::default() };
+
+        let config = AltiusEvmConfig::mainnet();
+        assert!(config.evm_env(&header).cfg_env.spec < SpecId::SHANGHAI);
+
+        let config = config.with_spec_override(Some(SpecId::SHANGHAI));
+        assert_eq!(config.evm_env(&header).cfg_env.spec, SpecId::SHANGHAI);
+
+        let next = config.next_evm_env(&header, &next_block_attributes(&header)).unwrap();
+        assert_eq!(next.cfg_env.spec, SpecId::SHANGHAI);
+    }
+
+    #[test]
+    fn spec_at_fork_boundaries() {
+        let config = AltiusEvmConfig::mainnet();
+
+        // London activates at block 12_965_000
+        let berlin = Header { number: 12_964_999, ..Header::default() };
+        let london = Header { number: 12_965_000, ..Header::default() };
+        assert_eq!(config.spec_for_header(&berlin), SpecId::BERLIN);
+        assert_eq!(config.spec_for_header(&london), SpecId::LONDON);
+        assert_eq!(
+            config.spec_for_next_block(&berlin, &next_block_attributes(&berlin)),
+            SpecId::LONDON
+        );
+
+        // Shanghai activates at timestamp 1_681_338_455
+        let paris = Header { number: 17_034_869, timestamp: 1_681_338_443, ..Header::default() };
+        let shanghai = Header { number: 17_034_870, timestamp: 1_681_338_455, ..Header::default() };
+        assert_eq!(config.spec_for_header(&paris), SpecId::MERGE);
+        assert_eq!(config.spec_for_header(&shanghai), SpecId::SHANGHAI);
+        assert_eq!(
+            config.spec_for_next_block(&paris, &next_block_attributes(&paris)),
+            SpecId::SHANGHAI
+        );
+
+        // The environments use the same specs
+        assert_eq!(config.evm_env(&london).cfg_env.spec, SpecId::LONDON);
+        let next = config.next_evm_env(&paris, &next_block_attributes(&paris)).unwrap();
+        assert_eq!(next.cfg_env.spec, SpecId::SHANGHAI);
+
+        // An override wins over the schedule
+        let config = config.with_spec_override(Some(SpecId::CANCUN));
+        assert_eq!(config.spec_for_header(&berlin), SpecId::CANCUN);
+        assert_eq!(
+            config.spec_for_next_block(&berlin, &next_block_attributes(&berlin)),
+            SpecId::CANCUN
+        );
+    }
+
+    #[test]
+    fn beneficiary_override_replaces_only_the_beneficiary() {
+        let coinbase = address!("0x00000000000000000000000000000000000000c0");
+        let header = Header {
+            number: 20_000_000,
+            timestamp: 1_718_000_000,
+            beneficiary: Address::repeat_byte(0x11),
+            gas_limit: 30_000_000,
+            base_fee_per_gas: Some(7),
+            ..Header::default()
+        };
+        let attributes = next_block_attributes(&header);
+
+        let config = AltiusEvmConfig::mainnet();
+        let original = config.evm_env(&header).block_env;
+        let original_next = config.next_evm_env(&header, &attributes).unwrap().block_env;
+        assert_eq!(original.beneficiary, header.beneficiary);
+        assert_eq!(original_next.beneficiary, attributes.suggested_fee_recipient);
+
+        let config = config.with_beneficiary_override(Some(coinbase));
+
+        let env = config.evm_env(&header).block_env;
+        assert_eq!(env.beneficiary, coinbase);
+        assert_eq!(BlockEnv { beneficiary: original.beneficiary, ..env }, original);
+
+        let next = config.next_evm_env(&header, &attributes).unwrap().block_env;
+        assert_eq!(next.beneficiary, coinbase);
+        assert_eq!(BlockEnv { beneficiary: original_next.beneficiary, ..next }, original_next);
+    }
+
+    #[test]
+    fn chain_id_override_propagates() {
+        let header = Header { number: 1, timestamp: 1, ..Header::default() };
+
+        let config = AltiusEvmConfig::mainnet();
+        let spec = config.evm_env(&header).cfg_env.spec;
+        assert_eq!(config.evm_env(&header).cfg_env.chain_id, 1);
+
+        let config = config.with_chain_id_override(Some(1337));
+        let env = config.evm_env(&header);
+        assert_eq!(env.cfg_env.chain_id, 1337);
+        // Fork selection still follows mainnet
+        assert_eq!(env.cfg_env.spec, spec);
+
+        let next = config.next_evm_env(&header, &next_block_attributes(&header)).unwrap();
+        assert_eq!(next.cfg_env.chain_id, 1337);
+    }
+
+    #[test]
+    fn cfg_env_for_header_matches_evm_env() {
+        let headers = [
+            Header { number: 1, timestamp: 1, ..Header::default() },
+            Header { number: 20_000_000, timestamp: 1_710_338_135, ..Header::default() },
+        ];
+        let configs = [
+            AltiusEvmConfig::mainnet(),
+            AltiusEvmConfig::mainnet()
+                .with_spec_override(Some(SpecId::SHANGHAI))
+                .with_chain_id_override(Some(1337))
+                .with_disable_balance_check(true),
+        ];
+
+        for config in &configs {
+            for header in &headers {
+                assert_eq!(config.cfg_env_for_header(header), config.evm_env(header).cfg_env);
+            }
+        }
+    }
+
+    #[test]
+    fn validation_flags_appear_in_cfg_env() {
+        let header = Header { number: 1, timestamp: 1, ..Header::default() };
+
+        let config = AltiusEvmConfig::mainnet();
+        let cfg_env = config.evm_env(&header).cfg_env;
+        assert!(!cfg_env.disable_base_fee);
+        assert!(!cfg_env.disable_balance_check);
+
+        let config = config.with_disable_base_fee(true).with_disable_balance_check(true);
+        let cfg_env = config.evm_env(&header).cfg_env;
+        assert!(cfg_env.disable_base_fee);
+        assert!(cfg_env.disable_balance_check);
+
+        let next = config.next_evm_env(&header, &next_block_attributes(&header)).unwrap();
+        assert!(next.cfg_env.disable_base_fee);
+        assert!(next.cfg_env.disable_balance_check);
+    }
+
+    #[test]
+    fn gas_limit_cap_only_lowers_limit() {
+        let parent = Header { number: 1, timestamp: 1, gas_limit: 10_000_000, ..Header::default() };
+        let attributes = next_block_attributes(&parent);
+
+        let config = AltiusEvmConfig::mainnet();
+        let uncapped = config.next_evm_env(&parent, &attributes).unwrap().block_env;
+        assert_eq!(uncapped.gas_limit, 10_000_000);
+
+        let capped = config.clone().with_gas_limit_cap(Some(8_000_000));
+        let env = capped.next_evm_env(&parent, &attributes).unwrap().block_env;
+        assert_eq!(env.gas_limit, 8_000_000);
+        assert_eq!(env.basefee, uncapped.basefee);
+
+        let loose = config.with_gas_limit_cap(Some(30_000_000));
+        let env = loose.next_evm_env(&parent, &attributes).unwrap().block_env;
+        assert_eq!(env.gas_limit, 10_000_000);
+    }
+
+    #[test]
+    fn gas_limit_cap_at_london_boundary() {
+        let chain_spec = Arc::new(
+            ChainSpecBuilder::mainnet()
+                .berlin_activated()
+                .with_fork(EthereumHardfork::London, ForkCondition::Block(2))
+                .build(),
+        );
+        let parent = Header { number: 1, timestamp: 1, gas_limit: 10_000_000, ..Header::default() };
+        let attributes = next_block_attributes(&parent);
+
+        // Elasticity doubles the limit at the boundary
+        let config = AltiusEvmConfig::new(chain_spec);
+        let env = config.next_evm_env(&parent, &attributes).unwrap().block_env;
+        assert_eq!(env.gas_limit, 20_000_000);
+        assert_eq!(env.basefee, INITIAL_BASE_FEE);
+
+        // The cap applies to the doubled value, the base fee still starts at its initial value
+        let config = config.with_gas_limit_cap(Some(15_000_000));
+        let env = config.next_evm_env(&parent, &attributes).unwrap().block_env;
+        assert_eq!(env.gas_limit, 15_000_000);
+        assert_eq!(env.basefee, INITIAL_BASE_FEE);
+    }
+
+    #[test]
+    fn blob_params_override_changes_blob_price() {
+        let chain_spec = Arc::new(ChainSpecBuilder::mainnet().cancun_activated().build());
+        let header = Header {
+            number: 1,
+            timestamp: 1,
+            excess_blob_gas: Some(10_000_000),
+            ..Header::default()
+        };
+
+        let config = AltiusEvmConfig::new(chain_spec);
+        let default_price = config.evm_env(&header).block_env.blob_excess_gas_and_price.unwrap();
+        assert_eq!(default_price.blob_gasprice, BlobParams::cancun().calc_blob_fee(10_000_000));
+
+        let config = config.with_blob_params_override(Some(BlobParams::prague()));
+        let overridden = config.evm_env(&header).block_env.blob_excess_gas_and_price.unwrap();
+
+        // Same excess blob gas from the header, different price
+        assert_eq!(overridden.excess_blob_gas, default_price.excess_blob_gas);
+        assert_eq!(overridden.blob_gasprice, BlobParams::prague().calc_blob_fee(10_000_000));
+        assert_ne!(overridden.blob_gasprice, default_price.blob_gasprice);
+    }
+
+    #[test]
+    fn next_block_context_includes_ommers() {
+        let parent = SealedHeader::seal_slow(Header { number: 1, ..Header::default() });
+        let attributes = next_block_attributes(parent.header());
+
+        let config = AltiusEvmConfig::mainnet();
+        assert!(config.context_for_next_block(&parent, attributes.clone()).ommers.is_empty());
+
+        let ommer =
+            Header { number: 1, extra_data: Bytes::from_static(b"uncle"), ..Header::default() };
+        let config = config.with_next_block_ommers(vec![ommer.clone()]);
+        let ctx = config.context_for_next_block(&parent, attributes);
+        assert_eq!(ctx.ommers, &[ommer]);
+    }
+
+    #[test]
+    fn named_network_constructors() {
+        assert_eq!(AltiusEvmConfig::mainnet().chain_spec().chain().id(), 1);
+        assert_eq!(AltiusEvmConfig::sepolia().chain_spec().chain().id(), 11155111);
+        assert_eq!(AltiusEvmConfig::holesky().chain_spec().chain().id(), 17000);
+
+        for chain in
+            [NamedChain::Mainnet, NamedChain::Sepolia, NamedChain::Holesky, NamedChain::Hoodi]
+        {
+            let config = AltiusEvmConfig::from_named_chain(chain).unwrap();
+            assert_eq!(config.chain_spec().chain().id(), chain as u64);
+        }
+
+        assert!(AltiusEvmConfig::from_named_chain(NamedChain::Optimism).is_none());
+    }
+
+    #[test]
+    fn extra_data_length_is_validated() {
+        let config = AltiusEvmConfig::mainnet()
+            .try_with_extra_data(Bytes::from(vec![0u8; MAXIMUM_EXTRA_DATA_SIZE]))
+            .unwrap();
+        assert_eq!(config.block_assembler.extra_data.len(), MAXIMUM_EXTRA_DATA_SIZE);
+
+        let err = config.try_with_extra_data(Bytes::from(vec![0u8; 33])).unwrap_err();
+        assert_eq!(err, ExtraDataTooLong { len: 33 });
+    }
+}
@@ -0,0 +1,124 @@
+//! Execution observers for the Altius executor.
+
+use alloy_evm::block::StateChangeSource;
+use alloy_primitives::U256;
+use reth_evm::OnStateHook;
+use revm::state::EvmState;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+/// A callback that is called with each transaction's receipt once a block has executed.
+///
+/// Receipts are delivered in canonical transaction order, regardless of the order in which
+/// the parallel scheduler executed the transactions. The callback runs after the block, see
+/// `AltiusExecutor::execute_one_with_receipt_callback`.
+///
+/// Any `FnMut(usize, &R)` closure implements this trait.
+pub trait ReceiptCallback<R>: Send + 'static {
+    /// Invoked with the index of the transaction in the block and its receipt.
+    fn on_receipt(&mut self, tx_index: usize, receipt: &R);
+}
+
+impl<F, R> ReceiptCallback<R> for F
+where
+    F: FnMut(usize, &R) + Send + 'static,
+{
+    fn on_receipt(&mut self, tx_index: usize, receipt: &R) {
+        self(tx_index, receipt)
+    }
+}
+
+/// A state hook that forwards every state update to several hooks.
+///
+/// Hooks are called in the order they were added, each with the same source and state. Pass
+/// it wherever a single [`OnStateHook`] is expected to attach several observers at once, for
+/// example metrics, a tracer and an indexer.
+#[derive(Default)]
+pub struct CompositeStateHook {
+    hooks: Vec<Box<dyn OnStateHook>>,
+}
+
+impl CompositeStateHook {
+    /// Creates a composite hook forwarding to `hooks`, in order.
+    pub fn new(hooks: Vec<Box<dyn OnStateHook>>) -> Self {
+        Self { hooks }
+    }
+
+    /// Adds a hook, called after the hooks already added.
+    pub fn with_hook(mut self, hook: impl OnStateHook) -> Self {
+        self.hooks.push(Box::new(hook));
+        self
+    }
+
+    /// Returns the number of hooks state updates are forwarded to.
+    pub fn len(&self) -> usize {
+        self.hooks.len()
+    }
+
+    /// Returns `true` if no hooks were added.
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+}
+
+impl core::fmt::Debug for CompositeStateHook {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("CompositeStateHook").field("hooks", &self.hooks.len()).finish()
+    }
+}
+
+impl OnStateHook for CompositeStateHook {
+    fn on_state(&mut self, source: StateChangeSource, state: &EvmState) {
+        for hook in &mut self.hooks {
+            hook.on_state(source, state);
+        }
+    }
+}
+
+/// Counts, per code hash, the transactions that loaded a contract.
+///
+/// Attached as a state hook by executors created with
+/// [`AltiusExecutor::with_hotness_tracking`](crate::AltiusExecutor::with_hotness_tracking).
+/// Clones share the same counts.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct HotnessCounter {
+    counts: Arc<Mutex<HashMap<U256, u64>>>,
+}
+
+impl HotnessCounter {
+    /// Counts every contract in the state of a transaction once.
+    pub(crate) fn record(&self, source: StateChangeSource, state: &EvmState) {
+        if !matches!(source, StateChangeSource::Transaction(_)) {
+            return
+        }
+
+        let mut counts = self.lock();
+        for account in state.values().filter(|account| !account.info.is_empty_code_hash()) {
+            *counts.entry(U256::from_be_bytes(account.info.code_hash.0)).or_default() += 1;
+        }
+    }
+
+    /// Returns the counts sorted by count, highest first, then by code hash.
+    pub(crate) fn report(&self) -> Vec<(U256, u64)> {
+        let mut report: Vec<_> = self.lock().iter().map(|(hash, count)| (*hash, *count)).collect();
+        report.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        report
+    }
+
+    /// Clears all counts.
+    pub(crate) fn reset(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<U256, u64>> {
+        self.counts.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl OnStateHook for HotnessCounter {
+    fn on_state(&mut self, source: StateChangeSource, state: &EvmState) {
+        self.record(source, state);
+    }
+}
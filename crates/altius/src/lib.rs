@@ -15,7 +15,14 @@
 //!
 //! - [`AltiusExecutor`]: The core block executor implementing parallel execution strategies
 //! - [`AltiusBlockExecutorProvider`]: Provider for creating configured block executors
+//! - [`SharedProvider`]: Thread-safe handle for spawning executors from several threads
 //! - [`config::AltiusEvmConfig`]: EVM configuration management
+//! - [`analysis::estimate_parallelism`]: Predicts the parallelism of transactions before execution
+//! - [`replay::replay_range`]: Replays a range of historical blocks from a provider
+//! - [`state::export_bundle`]: Saves the state changes of a run to a standalone file
+//! - [`trace::compare_traces`]: Checks opcode-level traces against geth's
+//! - [`trace::OpcodeCoverage`]: Counts executed opcodes, e.g. over a replayed block range
+//! - [`validate::precheck_block`]: Rejects malformed blocks before execution
 //!
 //! ## Example Usage
 //!
@@ -30,33 +37,94 @@
 //! let provider = AltiusBlockExecutorProvider::new(config);
 //! ```
 
-use alloy_evm::FromRecoveredTx;
+use alloy_consensus::{BlockHeader, TxReceipt};
+use alloy_eips::eip2930::{AccessList, AccessListItem};
+use alloy_evm::{block::StateChangeSource, FromRecoveredTx, IntoTxEnv};
+use alloy_primitives::{keccak256, Address, Bytes, B256, U256};
 use reth_evm::{
     execute::{BlockExecutionError, BlockExecutorFactory, Executor},
     ConfigureEvm,
     Database,
+    Evm,
+    EvmEnvFor,
     EvmFactory,
+    HaltReasonFor,
+    InspectorFor,
     OnStateHook,
+    TxEnvFor,
 };
 use reth_primitives_traits::{
+    Block,
+    BlockBody,
     NodePrimitives,
     RecoveredBlock,
 };
 use revm::{
-    database::{State, states::bundle_state::BundleRetention},
+    database::{
+        states::{bundle_state::BundleRetention, AccountRevert},
+        BundleState, CacheState, State, TransitionState,
+    },
     context::TxEnv,
-    primitives::hardfork::SpecId
+    context_interface::result::{ExecutionResult, ResultAndState},
+    primitives::hardfork::SpecId,
+    state::{Account, Bytecode, EvmState, EvmStorageSlot},
+    Database as _,
+    DatabaseCommit,
 };
 use reth_evm::execute::{BlockExecutorProvider, BlockExecutor};
 use core::fmt::Debug;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
 use reth_execution_types::BlockExecutionResult;
 use reth_db::mdbx::tx_pool;
 
+/// Conflict analysis of transactions.
+///
+/// This module estimates how well a list of transactions parallelizes from their expected
+/// accesses, without executing them.
+pub mod analysis;
+
 /// Altius EVM configuration and setup utilities.
 ///
 /// This module contains the configuration structures and methods needed to set up
 /// the Altius EVM with custom parameters, chain specifications, and execution factories.
 pub mod config;
+use config::AltiusEvmConfig;
+
+/// Hooks for observing block execution.
+///
+/// This module contains the observer traits that can be attached to an `AltiusExecutor`
+/// in addition to the standard `OnStateHook`.
+pub mod hooks;
+pub use hooks::{CompositeStateHook, ReceiptCallback};
+use hooks::HotnessCounter;
+
+/// Replay of historical blocks.
+///
+/// This module drives an `AltiusExecutor` over a range of blocks read from a provider,
+/// for benchmarking and for comparing results against other executors.
+pub mod replay;
+
+/// Standalone bundle state files.
+///
+/// This module exports the state changes of an `AltiusExecutor` to a versioned file and
+/// imports them back, for diffing runs outside of the node's database.
+pub mod state;
+
+/// Opcode-level execution traces.
+///
+/// This module records the steps of a transaction in the shape of geth's struct logs and
+/// compares them with traces captured from geth, reporting the first divergent step.
+pub mod trace;
+
+/// Pre-execution block checks.
+///
+/// This module rejects malformed blocks with a clear error before they reach the executor.
+pub mod validate;
+use validate::PrecheckError;
 
 /// A high-performance parallel block executor for the Altius implementation.
 ///
@@ -95,8 +163,211 @@ pub struct AltiusExecutor<F, DB: Database> {
     /// This maintains the current state of the blockchain and manages state
     /// transitions during block execution.
     pub(crate) db: State<DB>,
+
+    /// Whether every block is re-executed sequentially to check that the parallel execution
+    /// used the same amount of gas.
+    pub(crate) gas_audit: bool,
+
+    /// Per code hash counts of the transactions loading a contract, collected when hotness
+    /// tracking is enabled.
+    pub(crate) hotness: Option<HotnessCounter>,
+
+    /// Index in the bundle's reverts of the last successfully executed block.
+    pub(crate) last_block_reverts: Option<usize>,
+
+    /// Whether a summary line is logged for every successfully executed block.
+    pub(crate) block_summary: bool,
+
+    /// Whether the caller resets the global transaction manager instead of the executor.
+    ///
+    /// When set, the caller must reset the manager after every block this executor runs,
+    /// including failed blocks, dry runs and gas audits, before the next block starts and
+    /// never while a block is executing. See [`AltiusExecutor::with_external_tx_manager`].
+    pub(crate) manage_tx_manager_externally: bool,
+
+    /// Resets the global transaction manager after a block; replaced by tests to count resets.
+    #[cfg(test)]
+    pub(crate) reset_tx_manager: fn(),
+    /// Maximum number of transactions of a block this executor accepts.
+    pub(crate) max_transactions: Option<usize>,
+}
+
+/// Resets the global MDBX transaction manager shared by the parallel workers.
+fn reset_global_tx_manager() {
+    let _ = tx_pool::global_tx_manager().reset_tx();
+}
+
+/// Settings of the `State` an [`AltiusExecutor`] is built on.
+///
+/// Passed to [`AltiusExecutor::new_with_state_config`]. The default matches
+/// [`AltiusExecutor::new`]: bundle updates enabled and state clearing disabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StateConfig {
+    /// Whether changes are accumulated into a `BundleState`. Required for the executor's
+    /// results to be written to the database.
+    pub bundle_update: bool,
+    /// Whether touched empty accounts are removed, as specified by EIP-161. Block executors
+    /// usually set this per block according to the active hardfork, so enabling it here
+    /// matters for state changes committed outside of block execution.
+    pub state_clear: bool,
+}
+
+impl Default for StateConfig {
+    fn default() -> Self {
+        Self { bundle_update: true, state_clear: false }
+    }
+}
+
+impl StateConfig {
+    /// Enables or disables accumulating changes into a `BundleState`.
+    pub const fn with_bundle_update(mut self, bundle_update: bool) -> Self {
+        self.bundle_update = bundle_update;
+        self
+    }
+
+    /// Enables or disables EIP-161 state clearing.
+    pub const fn with_state_clear(mut self, state_clear: bool) -> Self {
+        self.state_clear = state_clear;
+        self
+    }
+}
+
+/// The state changed by a system call made while executing a block.
+///
+/// Returned by [`AltiusExecutor::execute_one_with_system_calls`].
+#[derive(Debug, Clone)]
+pub struct SystemCallChanges {
+    /// The system call, e.g. the EIP-4788 beacon root contract call before the transactions.
+    pub source: StateChangeSource,
+    /// The accounts and storage slots loaded or changed by the call.
+    pub state: EvmState,
+}
+
+/// A difference in gas usage between the parallel and the sequential execution of a block.
+///
+/// Reported, wrapped in a `BlockExecutionError`, by executors created with
+/// [`AltiusExecutor::with_gas_audit`]. Any mismatch points at a scheduler bug, e.g. refunds
+/// applied in the wrong order, and would make the block's receipts diverge from consensus.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum GasMismatch {
+    /// The cumulative gas of a transaction's receipt differs.
+    #[error(
+        "cumulative gas of transaction {tx_index} differs: parallel {parallel}, sequential {sequential}"
+    )]
+    Transaction {
+        /// The index of the first transaction whose cumulative gas differs.
+        tx_index: usize,
+        /// The cumulative gas after the parallel execution.
+        parallel: u64,
+        /// The cumulative gas after the sequential execution.
+        sequential: u64,
+    },
+    /// The gas used by the whole block differs.
+    #[error("block gas used differs: parallel {parallel}, sequential {sequential}")]
+    Block {
+        /// The gas used by the parallel execution.
+        parallel: u64,
+        /// The gas used by the sequential execution.
+        sequential: u64,
+    },
+}
+
+/// Compares the gas usage of two executions of the same block, receipt by receipt.
+fn find_gas_mismatch<R: TxReceipt>(
+    parallel: &BlockExecutionResult<R>,
+    sequential: &BlockExecutionResult<R>,
+) -> Option<GasMismatch> {
+    let transaction = parallel
+        .receipts
+        .iter()
+        .zip(&sequential.receipts)
+        .map(|(parallel, sequential)| {
+            (parallel.cumulative_gas_used(), sequential.cumulative_gas_used())
+        })
+        .enumerate()
+        .find(|(_, (parallel, sequential))| parallel != sequential)
+        .map(|(tx_index, (parallel, sequential))| GasMismatch::Transaction {
+            tx_index,
+            parallel,
+            sequential,
+        });
+
+    transaction.or_else(|| {
+        (parallel.gas_used != sequential.gas_used).then_some(GasMismatch::Block {
+            parallel: parallel.gas_used,
+            sequential: sequential.gas_used,
+        })
+    })
+}
+
+/// A point-in-time copy of an `AltiusExecutor`'s state.
+///
+/// Created by [`AltiusExecutor::clone_state`] and consumed by [`AltiusExecutor::restore`] to
+/// roll the executor back, e.g. to try alternative transaction orderings without re-executing
+/// everything from scratch.
+///
+/// # Memory
+///
+/// The snapshot owns a deep copy of the executor's cached accounts and storage, of its
+/// accumulated bundle state and of any pending transitions, so both its size and the time to
+/// take it grow with the amount of state touched since the executor was created. Keep
+/// snapshots short-lived on long batches.
+#[derive(Debug, Clone)]
+pub struct StateSnapshot {
+    /// Cached accounts, storage and contracts.
+    cache: CacheState,
+    /// Transitions not yet merged into the bundle.
+    transition_state: Option<TransitionState>,
+    /// Accumulated bundle state, including reverts.
+    bundle_state: BundleState,
+}
+
+/// Values replacing parts of an account's state, in the style of `eth_call` state overrides.
+///
+/// Fields left as `None` keep the account's current value. Storage overrides replace single
+/// slots, the other slots of the account are untouched.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AccountOverride {
+    /// The account's balance.
+    pub balance: Option<U256>,
+    /// The account's nonce.
+    pub nonce: Option<u64>,
+    /// The account's bytecode.
+    pub code: Option<Bytes>,
+    /// Storage slots and the values they hold.
+    pub storage: BTreeMap<U256, U256>,
+}
+
+impl AccountOverride {
+    /// Overrides the account's balance.
+    pub const fn with_balance(mut self, balance: U256) -> Self {
+        self.balance = Some(balance);
+        self
+    }
+
+    /// Overrides the account's nonce.
+    pub const fn with_nonce(mut self, nonce: u64) -> Self {
+        self.nonce = Some(nonce);
+        self
+    }
+
+    /// Overrides the account's bytecode.
+    pub fn with_code(mut self, code: Bytes) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    /// Overrides the value of a storage slot.
+    pub fn with_storage(mut self, slot: U256, value: U256) -> Self {
+        self.storage.insert(slot, value);
+        self
+    }
 }
 
+/// Account overrides applied by [`AltiusExecutor::apply_overrides`] and
+/// [`AltiusExecutor::execute_one_with_overrides`], keyed by address.
+pub type StateOverrides = BTreeMap<Address, AccountOverride>;
+
 impl<F: Debug, DB: Database> Debug for AltiusExecutor<F, DB> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("AltiusExecutor")
@@ -128,8 +399,670 @@ impl<F, DB: Database> AltiusExecutor<F, DB> {
     /// - State clearing disabled to preserve intermediate states
     /// - Optimized caching for high-throughput scenarios
     pub fn new(strategy_factory: F, db: DB) -> Self {
-        let db = State::builder().with_database(db).with_bundle_update().without_state_clear().build();
-        Self { strategy_factory, db }
+        Self::new_with_state_config(strategy_factory, db, StateConfig::default())
+    }
+
+    /// Creates a new `AltiusExecutor` whose state is built with the given settings.
+    ///
+    /// Use this instead of [`Self::new`] when the default state configuration does not fit,
+    /// e.g. to enable EIP-161 state clearing for some replay scenarios.
+    ///
+    /// # Parameters
+    ///
+    /// * `strategy_factory` - The factory that will create execution strategies for blocks
+    /// * `db` - The underlying database for state storage and retrieval
+    /// * `config` - The settings of the `State` wrapping `db`
+    ///
+    /// # Returns
+    ///
+    /// A new `AltiusExecutor` instance ready for block execution
+    pub fn new_with_state_config(strategy_factory: F, db: DB, config: StateConfig) -> Self {
+        let mut builder = State::builder().with_database(db);
+        if config.bundle_update {
+            builder = builder.with_bundle_update();
+        }
+        if !config.state_clear {
+            builder = builder.without_state_clear();
+        }
+
+        Self {
+            strategy_factory,
+            db: builder.build(),
+            gas_audit: false,
+            hotness: None,
+            last_block_reverts: None,
+            block_summary: true,
+            manage_tx_manager_externally: false,
+            #[cfg(test)]
+            reset_tx_manager: reset_global_tx_manager,
+            max_transactions: None,
+        }
+    }
+
+    /// Enables or disables the gas audit.
+    ///
+    /// With the audit enabled, every block executed through [`Executor::execute_one`] or
+    /// [`Executor::execute_one_with_state_hook`] is executed a second time, sequentially, from
+    /// the same starting state. If the cumulative gas of any receipt or the block's gas used
+    /// differs, execution fails with a [`GasMismatch`]. This roughly doubles the cost of each
+    /// block and is meant for testing and canary nodes, not for regular sync.
+    ///
+    /// # Parameters
+    ///
+    /// * `enabled` - Whether to audit the gas usage of every block
+    ///
+    /// # Returns
+    ///
+    /// The executor with the audit setting applied
+    pub fn with_gas_audit(mut self, enabled: bool) -> Self {
+        self.gas_audit = enabled;
+        self
+    }
+
+    /// Enables or disables the per-block summary log.
+    ///
+    /// When enabled, which is the default, every block executed through
+    /// [`Executor::execute_one`] or [`Executor::execute_one_with_state_hook`] logs one line at
+    /// `info` level with target `altius::executor`. Its fields are the block number, the
+    /// number of transactions, the gas used and the execution time, so operators can follow
+    /// progress without the chrome-trace profiler. The line can also be silenced through the
+    /// log filter, e.g. `RUST_LOG=altius::executor=warn`.
+    ///
+    /// # Parameters
+    ///
+    /// * `enabled` - Whether to log a summary of every block
+    ///
+    /// # Returns
+    ///
+    /// The executor with the summary setting applied
+    pub fn with_block_summary(mut self, enabled: bool) -> Self {
+        self.block_summary = enabled;
+        self
+    }
+
+    /// Leaves resetting the global transaction manager to the caller.
+    ///
+    /// With the parallel state provider (`ENABLE_THREAD_TX`), the workers read the database
+    /// through read transactions handed out by `tx_pool::global_tx_manager()`. By default the
+    /// executor resets the manager after every block it executes, so that the next block
+    /// opens fresh transactions. Embedders that manage this state themselves can disable the
+    /// reset, and must then uphold what it guarantees:
+    ///
+    /// - the manager is reset after every block executed by this executor, whether it
+    ///   succeeded or not, including dry runs, gas audits and inspected executions;
+    /// - the reset happens before the next block starts, otherwise that block reads through
+    ///   transactions that do not see the state committed since they were opened;
+    /// - the manager is never reset while a block is executing.
+    ///
+    /// # Parameters
+    ///
+    /// * `enabled` - Whether the caller resets the transaction manager
+    ///
+    /// # Returns
+    ///
+    /// The executor with the setting applied
+    pub fn with_external_tx_manager(mut self, enabled: bool) -> Self {
+        self.manage_tx_manager_externally = enabled;
+        self
+    }
+
+    /// Limits the number of transactions of the blocks this executor accepts.
+    ///
+    /// A block with more transactions fails with [`PrecheckError::ExceedsConfiguredLimit`]
+    /// before any EVM work is done, leaving the state untouched. Executors created by an
+    /// [`AltiusBlockExecutorProvider`] get the provider's limit.
+    ///
+    /// # Parameters
+    ///
+    /// * `limit` - The maximum number of transactions per block, or `None` for no limit
+    ///
+    /// # Returns
+    ///
+    /// The executor with the limit applied
+    pub fn with_max_transactions(mut self, limit: Option<usize>) -> Self {
+        self.max_transactions = limit;
+        self
+    }
+
+    /// Enables or disables counting, per contract, the transactions that load it.
+    ///
+    /// With tracking enabled, every account with code in a transaction's state is counted once
+    /// for that transaction, keyed by its code hash. These are per-transaction loads, not
+    /// calls: a contract called several times within one transaction counts once, and a
+    /// contract that is only inspected, e.g. with `EXTCODESIZE` or `BALANCE`, counts as well.
+    /// Counting calls needs an inspector, which the parallel scheduler cannot run. The counts
+    /// accumulate across blocks until [`Self::reset_hotness`] is called and are read with
+    /// [`Self::contract_load_counts`]. They can guide which contracts are worth building SSA
+    /// graphs for. Disabling tracking drops the counts collected so far.
+    ///
+    /// # Parameters
+    ///
+    /// * `enabled` - Whether to count contract loads
+    ///
+    /// # Returns
+    ///
+    /// The executor with the tracking setting applied
+    pub fn with_hotness_tracking(mut self, enabled: bool) -> Self {
+        self.hotness = enabled.then(HotnessCounter::default);
+        self
+    }
+
+    /// Returns, for every contract seen since tracking was enabled or last reset, the number of
+    /// transactions that loaded it, as `(code_hash, count)` pairs sorted by count, highest
+    /// first.
+    ///
+    /// Empty when hotness tracking is disabled.
+    pub fn contract_load_counts(&self) -> Vec<(U256, u64)> {
+        self.hotness.as_ref().map(HotnessCounter::report).unwrap_or_default()
+    }
+
+    /// Clears the load counts, keeping tracking enabled.
+    pub fn reset_hotness(&mut self) {
+        if let Some(hotness) = &self.hotness {
+            hotness.reset();
+        }
+    }
+
+    /// Deep-clones the executor's current state.
+    ///
+    /// This copies every cached account, storage slot and contract, the bundle state and the
+    /// pending transitions, so it is not cheap on long batches. It cannot be made
+    /// copy-on-write without changing revm's `State`, which owns these maps directly. See
+    /// [`StateSnapshot`] for the memory implications.
+    pub fn clone_state(&self) -> StateSnapshot {
+        StateSnapshot {
+            cache: self.db.cache.clone(),
+            transition_state: self.db.transition_state.clone(),
+            bundle_state: self.db.bundle_state.clone(),
+        }
+    }
+
+    /// Rolls the executor's state back to a state cloned with [`Self::clone_state`].
+    ///
+    /// Everything executed since the state was cloned is discarded.
+    pub fn restore(&mut self, snapshot: StateSnapshot) {
+        let StateSnapshot { cache, transition_state, bundle_state } = snapshot;
+        self.db.cache = cache;
+        self.db.transition_state = transition_state;
+        self.db.bundle_state = bundle_state;
+        self.last_block_reverts = None;
+    }
+
+    /// Applies state overrides to the executor's state.
+    ///
+    /// The overrides are committed like the changes of a transaction: blocks executed
+    /// afterwards see them, and they end up in the bundle with the next block's changes. Use
+    /// [`Self::execute_one_with_overrides`] to apply them to a single block only.
+    ///
+    /// # Returns
+    ///
+    /// An error if the current values of an overridden account could not be read
+    pub fn apply_overrides(
+        &mut self,
+        overrides: &StateOverrides,
+    ) -> Result<(), BlockExecutionError> {
+        let mut state = EvmState::default();
+        for (address, account_override) in overrides {
+            let info = self.db.basic(*address).map_err(BlockExecutionError::other)?;
+            let mut account = Account::from(info.unwrap_or_default());
+            if let Some(balance) = account_override.balance {
+                account.info.balance = balance;
+            }
+            if let Some(nonce) = account_override.nonce {
+                account.info.nonce = nonce;
+            }
+            if let Some(code) = &account_override.code {
+                account.info.code_hash = keccak256(code);
+                account.info.code = Some(Bytecode::new_raw(code.clone()));
+            }
+            for (slot, value) in &account_override.storage {
+                let original =
+                    self.db.storage(*address, *slot).map_err(BlockExecutionError::other)?;
+                account.storage.insert(*slot, EvmStorageSlot::new_changed(original, *value));
+            }
+            account.mark_touch();
+            state.insert(*address, account);
+        }
+
+        self.db.commit(state);
+        Ok(())
+    }
+
+    /// Returns the reverts of the last block executed by this executor.
+    ///
+    /// Each entry pairs an account with what is needed to roll it back to its state before the
+    /// block: its previous info, its previous storage values and whether it has to be deleted.
+    /// Entries are sorted by address and serialize with serde, for reorg tooling and
+    /// explorers. The list is empty before the first block, after a block failed and after
+    /// [`Self::restore`].
+    pub fn reverts_for_last_block(&self) -> Vec<(Address, AccountRevert)> {
+        let mut reverts = self
+            .last_block_reverts
+            .and_then(|index| self.db.bundle_state.reverts.get(index))
+            .cloned()
+            .unwrap_or_default();
+        reverts.sort_unstable_by_key(|(address, _)| *address);
+        reverts
+    }
+
+    /// Merges the transitions of the block that just executed into the bundle, keeping track of
+    /// its reverts if it succeeded.
+    fn merge_block_transitions(&mut self, succeeded: bool) {
+        self.db.merge_transitions(BundleRetention::Reverts);
+        self.last_block_reverts = succeeded
+            .then(|| self.db.bundle_state.reverts.len())
+            .and_then(|len| len.checked_sub(1));
+    }
+
+    /// Resets the global transaction manager after a block, unless the caller manages it.
+    fn reset_tx_manager(&self) {
+        if !self.manage_tx_manager_externally {
+            #[cfg(test)]
+            (self.reset_tx_manager)();
+            #[cfg(not(test))]
+            reset_global_tx_manager();
+        }
+    }
+
+    /// Logs the summary line of a successfully executed block, if enabled.
+    fn log_block_summary<B: Block, R>(
+        &self,
+        block: &RecoveredBlock<B>,
+        result: &BlockExecutionResult<R>,
+        elapsed: Duration,
+    ) {
+        if !self.block_summary {
+            return
+        }
+
+        tracing::info!(
+            target: "altius::executor",
+            block_number = block.header().number(),
+            tx_count = result.receipts.len(),
+            gas_used = result.gas_used,
+            elapsed_us = elapsed.as_micros() as u64,
+            "Executed block"
+        );
+    }
+}
+
+impl<F, DB> AltiusExecutor<F, DB>
+where
+    F: ConfigureEvm,
+    <F::BlockExecutorFactory as BlockExecutorFactory>::EvmFactory: EvmFactory<Tx = TxEnv, Spec = SpecId>,
+    TxEnv: FromRecoveredTx<<<F as ConfigureEvm>::Primitives as NodePrimitives>::SignedTx>,
+    DB: Database,
+{
+    /// Executes a block and returns its state changes without committing them.
+    ///
+    /// This is meant for previewing the effect of a block (simulation, what-if analysis). The
+    /// transactions run exactly as in [`Executor::execute_one`], but afterwards the cached state,
+    /// the pending transitions and the accumulated bundle are put back to what they were before
+    /// the call, so the executor can keep going as if the block was never executed. Changes
+    /// committed before the call but not yet merged, such as [`Self::apply_overrides`], are
+    /// part of the starting state and not of the returned diff.
+    ///
+    /// # Returns
+    ///
+    /// A `BundleState` holding only the changes made by this block, including reverts
+    pub fn dry_run(
+        &mut self,
+        block: &RecoveredBlock<<F::Primitives as NodePrimitives>::Block>,
+    ) -> Result<BundleState, BlockExecutionError> {
+        self.precheck(block)?;
+
+        // Keep the committed state and any pending transitions, e.g. of applied overrides,
+        // aside so the block's changes land in an empty bundle
+        let cache = self.db.cache.clone();
+        let transition_state = self.db.transition_state.as_mut().map(core::mem::take);
+        let bundle_state = core::mem::take(&mut self.db.bundle_state);
+
+        let result = self
+            .strategy_factory
+            .executor_for_block(&mut self.db, block)
+            .execute_block(block.transactions_recovered());
+
+        self.reset_tx_manager();
+
+        // Collect the block's transitions, then restore the previous state
+        self.db.merge_transitions(BundleRetention::Reverts);
+        let diff = core::mem::replace(&mut self.db.bundle_state, bundle_state);
+        self.db.transition_state = transition_state;
+        self.db.cache = cache;
+
+        result.map(|_| diff)
+    }
+
+    /// Executes a block on top of the current state with the given overrides applied.
+    ///
+    /// This is the block-level equivalent of `eth_call` state overrides, e.g. to simulate a
+    /// block as if an account held more funds. Like [`Self::dry_run`], the executor's state is
+    /// put back to what it was before the call afterwards, discarding both the overrides and
+    /// the block's changes. To keep them, call [`Self::apply_overrides`] and then execute the
+    /// block normally.
+    ///
+    /// # Parameters
+    ///
+    /// * `block` - The recovered block to execute
+    /// * `overrides` - The account overrides applied before the block
+    ///
+    /// # Returns
+    ///
+    /// The result of executing the block on the overridden state
+    pub fn execute_one_with_overrides(
+        &mut self,
+        block: &RecoveredBlock<<F::Primitives as NodePrimitives>::Block>,
+        overrides: StateOverrides,
+    ) -> Result<BlockExecutionResult<<F::Primitives as NodePrimitives>::Receipt>, BlockExecutionError>
+    {
+        let snapshot = self.clone_state();
+        let result = self.apply_overrides(&overrides).and_then(|()| self.execute_one(block));
+        self.restore(snapshot);
+
+        result
+    }
+
+    /// Executes a single transaction against the current state, outside of any block.
+    ///
+    /// The transaction runs on a plain EVM built from `evm_env`, bypassing the parallel
+    /// scheduler, and its state changes are committed to the executor's state. No block-level
+    /// work happens: no system calls, no rewards or withdrawals and no receipts.
+    ///
+    /// This is intended for tooling such as debuggers and tracers that need to step through a
+    /// block one transaction at a time. It must not be used for consensus execution.
+    ///
+    /// # Parameters
+    ///
+    /// * `evm_env` - The environment of the block the transaction belongs to
+    /// * `tx` - The transaction to execute, e.g. a recovered transaction of the block
+    pub fn execute_transaction(
+        &mut self,
+        evm_env: EvmEnvFor<F>,
+        tx: impl IntoTxEnv<TxEnvFor<F>>,
+    ) -> Result<ExecutionResult<HaltReasonFor<F>>, BlockExecutionError> {
+        let mut evm = self.strategy_factory.evm_with_env(&mut self.db, evm_env);
+        let ResultAndState { result, state } =
+            evm.transact(tx).map_err(BlockExecutionError::other)?;
+        drop(evm);
+
+        self.db.commit(state);
+
+        Ok(result)
+    }
+
+    /// Executes a single transaction like [`Self::execute_transaction`], with the given
+    /// inspector attached to the EVM.
+    ///
+    /// # Parameters
+    ///
+    /// * `evm_env` - The environment of the block the transaction belongs to
+    /// * `tx` - The transaction to execute, e.g. a recovered transaction of the block
+    /// * `inspector` - The inspector to attach, e.g. a [`trace::StepRecorder`]
+    ///
+    /// # Returns
+    ///
+    /// The execution result together with the inspector, holding whatever it collected
+    pub fn execute_transaction_with_inspector<I>(
+        &mut self,
+        evm_env: EvmEnvFor<F>,
+        tx: impl IntoTxEnv<TxEnvFor<F>>,
+        mut inspector: I,
+    ) -> Result<(ExecutionResult<HaltReasonFor<F>>, I), BlockExecutionError>
+    where
+        for<'a> &'a mut I: InspectorFor<F, &'a mut State<DB>>,
+    {
+        let mut evm =
+            self.strategy_factory.evm_with_env_and_inspector(&mut self.db, evm_env, &mut inspector);
+        let ResultAndState { result, state } =
+            evm.transact(tx).map_err(BlockExecutionError::other)?;
+        drop(evm);
+
+        self.db.commit(state);
+
+        Ok((result, inspector))
+    }
+
+    /// Executes a single block with the given inspector attached to the EVM.
+    ///
+    /// Inspectors observe every step of execution in order, which the parallel scheduler
+    /// cannot provide, so this runs the block **sequentially**: the pre-execution changes, then
+    /// each transaction in block order through the block executor's per-transaction path, then
+    /// the post-execution changes. The result and the resulting state are the same as with
+    /// `execute_one`, only slower; use it for tracing (`debug_traceBlock`-style tooling), not
+    /// for sync.
+    ///
+    /// # Parameters
+    ///
+    /// * `block` - The recovered block to execute
+    /// * `inspector` - The inspector to attach, e.g. a tracer
+    ///
+    /// # Returns
+    ///
+    /// The block execution result together with the inspector, holding whatever it collected
+    pub fn execute_one_with_inspector<I>(
+        &mut self,
+        block: &RecoveredBlock<<F::Primitives as NodePrimitives>::Block>,
+        mut inspector: I,
+    ) -> Result<
+        (BlockExecutionResult<<F::Primitives as NodePrimitives>::Receipt>, I),
+        BlockExecutionError,
+    >
+    where
+        for<'a> &'a mut I: InspectorFor<F, &'a mut State<DB>>,
+    {
+        self.precheck(block)?;
+
+        let evm_env = self.strategy_factory.evm_env(block.header());
+        let evm =
+            self.strategy_factory.evm_with_env_and_inspector(&mut self.db, evm_env, &mut inspector);
+        let ctx = self.strategy_factory.context_for_block(block);
+        let mut strategy = self.strategy_factory.create_executor(evm, ctx);
+
+        // The strategy is consumed by the closure, releasing the borrows of the state and
+        // the inspector
+        let result = strategy.apply_pre_execution_changes().and_then(|()| {
+            for tx in block.transactions_recovered() {
+                strategy.execute_transaction(tx)?;
+            }
+            strategy.apply_post_execution_changes()
+        });
+
+        self.reset_tx_manager();
+
+        self.merge_block_transitions(result.is_ok());
+
+        result.map(|result| (result, inspector))
+    }
+
+    /// Executes a single block and returns the accounts and storage slots its transactions
+    /// read or wrote, deduplicated and sorted by address and slot.
+    ///
+    /// The list is built from the per-transaction state reported through the state hook, so
+    /// accounts and slots that were only read are included. The scheduler's own read and
+    /// write sets are not exposed by `alloy-altius-evm` yet. Entries are aggregated across all
+    /// transactions, so the same block always yields the same list. State touched by pre- and
+    /// post-block system calls is left out.
+    ///
+    /// The block's beneficiary is left out as well: every transaction pays its fee to it, and
+    /// it is warm from the start of each transaction since EIP-3651, so listing it would only
+    /// add cost to a derived EIP-2930 list. This includes storage slots of the beneficiary a
+    /// transaction read explicitly.
+    ///
+    /// # Parameters
+    ///
+    /// * `block` - The recovered block to execute
+    ///
+    /// # Returns
+    ///
+    /// The block execution result together with the block's access list
+    pub fn execute_one_with_access_list(
+        &mut self,
+        block: &RecoveredBlock<<F::Primitives as NodePrimitives>::Block>,
+    ) -> Result<
+        (BlockExecutionResult<<F::Primitives as NodePrimitives>::Receipt>, AccessList),
+        BlockExecutionError,
+    > {
+        let accessed = Arc::new(Mutex::new(BTreeMap::<Address, BTreeSet<B256>>::new()));
+        let collector = accessed.clone();
+        let result = self.execute_one_with_state_hook(
+            block,
+            move |source: StateChangeSource, state: &EvmState| {
+                if let StateChangeSource::Transaction(_) = source {
+                    let mut accessed = collector.lock().unwrap_or_else(|e| e.into_inner());
+                    for (address, account) in state {
+                        accessed
+                            .entry(*address)
+                            .or_default()
+                            .extend(account.storage.keys().map(|slot| B256::from(*slot)));
+                    }
+                }
+            },
+        )?;
+
+        let mut accessed =
+            core::mem::take(&mut *accessed.lock().unwrap_or_else(|e| e.into_inner()));
+        accessed.remove(&self.strategy_factory.evm_env(block.header()).block_env.beneficiary);
+        let access_list = AccessList(
+            accessed
+                .into_iter()
+                .map(|(address, slots)| AccessListItem {
+                    address,
+                    storage_keys: slots.into_iter().collect(),
+                })
+                .collect(),
+        );
+
+        Ok((result, access_list))
+    }
+
+    /// Executes a single block and returns the state changes of its system calls.
+    ///
+    /// System calls run before and after the block's transactions: the EIP-4788 beacon root
+    /// and EIP-2935 block hash contract calls, then the request contract calls and withdrawals
+    /// after Prague. Their changes are reported through the state hook in the order they
+    /// happen and collected here, which helps diagnosing post-Cancun execution issues.
+    ///
+    /// A system call that reverts fails the whole block, e.g. with
+    /// `BlockValidationError::BeaconRootContractCall`, so every returned call succeeded. System
+    /// calls are not charged to the block, and their gas is not reported.
+    ///
+    /// # Parameters
+    ///
+    /// * `block` - The recovered block to execute
+    ///
+    /// # Returns
+    ///
+    /// The block execution result together with the changes of each system call, in order
+    pub fn execute_one_with_system_calls(
+        &mut self,
+        block: &RecoveredBlock<<F::Primitives as NodePrimitives>::Block>,
+    ) -> Result<
+        (BlockExecutionResult<<F::Primitives as NodePrimitives>::Receipt>, Vec<SystemCallChanges>),
+        BlockExecutionError,
+    > {
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        let collector = changes.clone();
+        let result = self.execute_one_with_state_hook(
+            block,
+            move |source: StateChangeSource, state: &EvmState| {
+                if !matches!(source, StateChangeSource::Transaction(_)) {
+                    collector
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .push(SystemCallChanges { source, state: state.clone() });
+                }
+            },
+        )?;
+
+        let changes = core::mem::take(&mut *changes.lock().unwrap_or_else(|e| e.into_inner()));
+        Ok((result, changes))
+    }
+
+    /// Re-executes `block` sequentially from `pre_state` and compares its gas usage with the
+    /// `parallel` result.
+    ///
+    /// The executor is left with the state produced by the parallel execution.
+    fn audit_gas(
+        &mut self,
+        block: &RecoveredBlock<<F::Primitives as NodePrimitives>::Block>,
+        pre_state: StateSnapshot,
+        parallel: &BlockExecutionResult<<F::Primitives as NodePrimitives>::Receipt>,
+    ) -> Result<(), BlockExecutionError> {
+        let post_state = self.clone_state();
+        let last_block_reverts = self.last_block_reverts;
+        self.restore(pre_state);
+
+        let evm_env = self.strategy_factory.evm_env(block.header());
+        let evm = self.strategy_factory.evm_with_env(&mut self.db, evm_env);
+        let ctx = self.strategy_factory.context_for_block(block);
+        let mut strategy = self.strategy_factory.create_executor(evm, ctx);
+        let sequential = strategy.apply_pre_execution_changes().and_then(|()| {
+            for tx in block.transactions_recovered() {
+                strategy.execute_transaction(tx)?;
+            }
+            strategy.apply_post_execution_changes()
+        });
+
+        self.reset_tx_manager();
+
+        self.restore(post_state);
+        self.last_block_reverts = last_block_reverts;
+
+        match find_gas_mismatch(parallel, &sequential?) {
+            Some(mismatch) => Err(BlockExecutionError::other(mismatch)),
+            None => Ok(()),
+        }
+    }
+
+    /// Executes a single block, then passes every receipt to the given callback.
+    ///
+    /// This is a post-block callback: it runs after the whole block has executed, with
+    /// `(tx_index, &receipt)` in canonical transaction order, and is not called at all if the
+    /// block fails. It does not deliver receipts any earlier than `execute_one`, it only saves
+    /// callers the loop over the result. Invoking it as each transaction commits needs the
+    /// scheduler's cooperation, see "Live receipt delivery" in `docs/altius/upstream.md`.
+    ///
+    /// # Parameters
+    ///
+    /// * `block` - The recovered block to execute
+    /// * `callback` - The callback receiving each transaction's receipt
+    ///
+    /// # Returns
+    ///
+    /// The block execution result, identical to what `execute_one` returns
+    pub fn execute_one_with_receipt_callback<C>(
+        &mut self,
+        block: &RecoveredBlock<<F::Primitives as NodePrimitives>::Block>,
+        mut callback: C,
+    ) -> Result<BlockExecutionResult<<F::Primitives as NodePrimitives>::Receipt>, BlockExecutionError>
+    where
+        C: ReceiptCallback<<F::Primitives as NodePrimitives>::Receipt>,
+    {
+        let result = self.execute_one(block)?;
+
+        for (tx_index, receipt) in result.receipts.iter().enumerate() {
+            callback.on_receipt(tx_index, receipt);
+        }
+
+        Ok(result)
+    }
+
+    /// Rejects blocks the executor should not start on: blocks over the transaction limit and
+    /// Cancun blocks without a parent beacon block root.
+    fn precheck(
+        &self,
+        block: &RecoveredBlock<<F::Primitives as NodePrimitives>::Block>,
+    ) -> Result<(), BlockExecutionError> {
+        let count = block.body().transactions().len();
+        if let Some(limit) = self.max_transactions.filter(|limit| count > *limit) {
+            return Err(BlockExecutionError::other(PrecheckError::ExceedsConfiguredLimit {
+                count,
+                limit,
+            }))
+        }
+
+        let spec = self.strategy_factory.evm_env(block.header()).cfg_env.spec;
+        validate::precheck_beacon_root(spec, block.header())
     }
 }
 
@@ -169,9 +1102,19 @@ where
         block: &RecoveredBlock<<Self::Primitives as NodePrimitives>::Block>,
     ) -> Result<BlockExecutionResult<<Self::Primitives as NodePrimitives>::Receipt>, Self::Error>
     {
+        self.precheck(block)?;
+
+        let pre_state = self.gas_audit.then(|| self.clone_state());
+        let started = Instant::now();
+
         // Step 1: Create the inner block executor using the strategy factory
         // This sets up the basic execution environment for the block
-        let strategy = self.strategy_factory.executor_for_block(&mut self.db, block);
+        let hotness_hook =
+            self.hotness.clone().map(|hotness| Box::new(hotness) as Box<dyn OnStateHook>);
+        let strategy = self
+            .strategy_factory
+            .executor_for_block(&mut self.db, block)
+            .with_state_hook(hotness_hook);
 
         
         // Step 2: Execute all transactions in the block using parallel execution
@@ -180,11 +1123,21 @@ where
 
         // Note: Post-execution changes and finalization are handled within the strategy
         // This includes state root calculation and receipt generation
-        let _ = tx_pool::global_tx_manager().reset_tx();
+        self.reset_tx_manager();
 
-        self.db.merge_transitions(BundleRetention::Reverts);
+        self.merge_block_transitions(result.is_ok());
+        if let Ok(result) = &result {
+            self.log_block_summary(block, result, started.elapsed());
+        }
 
-        result
+        match pre_state {
+            Some(pre_state) => {
+                let result = result?;
+                self.audit_gas(block, pre_state, &result)?;
+                Ok(result)
+            }
+            None => result,
+        }
     }
 
     /// Executes a single block with a custom state monitoring hook.
@@ -218,11 +1171,25 @@ where
     fn execute_one_with_state_hook<H>(
         &mut self,
         block: &RecoveredBlock<<Self::Primitives as NodePrimitives>::Block>,
-        state_hook: H,
+        mut state_hook: H,
     ) -> Result<BlockExecutionResult<<Self::Primitives as NodePrimitives>::Receipt>, Self::Error>
     where
         H: OnStateHook + 'static,
     {
+        self.precheck(block)?;
+
+        let pre_state = self.gas_audit.then(|| self.clone_state());
+        let started = Instant::now();
+
+        // Hotness is counted from the same state updates the caller's hook receives
+        let hotness = self.hotness.clone();
+        let state_hook = move |source: StateChangeSource, state: &EvmState| {
+            if let Some(hotness) = &hotness {
+                hotness.record(source, state);
+            }
+            state_hook.on_state(source, state);
+        };
+
         // Step 1: Create the inner block executor with state hook attached
         // The state hook will be called during execution to monitor state changes
         let strategy = self
@@ -236,11 +1203,21 @@ where
 
         // Note: The state hook provides real-time visibility into state changes
         // without affecting the execution performance significantly
-        let _ = tx_pool::global_tx_manager().reset_tx();
+        self.reset_tx_manager();
 
-        self.db.merge_transitions(BundleRetention::Reverts);
+        self.merge_block_transitions(result.is_ok());
+        if let Ok(result) = &result {
+            self.log_block_summary(block, result, started.elapsed());
+        }
 
-        result
+        match pre_state {
+            Some(pre_state) => {
+                let result = result?;
+                self.audit_gas(block, pre_state, &result)?;
+                Ok(result)
+            }
+            None => result,
+        }
     }
 
     /// Consumes the executor and returns the underlying database state.
@@ -305,6 +1282,9 @@ pub struct AltiusBlockExecutorProvider<F> {
     /// EVM configuration that will be applied to all blocks processed by executors
     /// created from this provider.
     strategy_factory: F,
+
+    /// Maximum number of transactions of a block the created executors accept.
+    max_transactions: Option<usize>,
 }
 
 impl<F> AltiusBlockExecutorProvider<F> {
@@ -326,7 +1306,51 @@ impl<F> AltiusBlockExecutorProvider<F> {
     /// The provider uses a const constructor to ensure minimal overhead when creating
     /// executor instances, making it suitable for high-frequency executor creation.
     pub const fn new(strategy_factory: F) -> Self {
-        Self { strategy_factory }
+        Self { strategy_factory, max_transactions: None }
+    }
+
+    /// Limits the number of transactions of the blocks the created executors accept.
+    ///
+    /// See [`AltiusExecutor::with_max_transactions`].
+    ///
+    /// # Parameters
+    ///
+    /// * `limit` - The maximum number of transactions per block, or `None` for no limit
+    ///
+    /// # Returns
+    ///
+    /// The provider with the limit applied
+    pub fn with_max_transactions(mut self, limit: Option<usize>) -> Self {
+        self.max_transactions = limit;
+        self
+    }
+
+    /// Converts the provider into a [`SharedProvider`] for spawning executors from several
+    /// threads.
+    pub fn into_shared(self) -> SharedProvider<F> {
+        SharedProvider {
+            strategy_factory: Arc::new(self.strategy_factory),
+            max_transactions: self.max_transactions,
+        }
+    }
+}
+
+impl<EvmF, R> AltiusBlockExecutorProvider<AltiusEvmConfig<EvmF, R>> {
+    /// Creates a provider from an Altius configuration, carrying over the limits set on it.
+    ///
+    /// Unlike [`Self::new`], the created executors reject blocks over the configuration's
+    /// [`AltiusEvmConfig::with_max_transactions`] limit.
+    ///
+    /// # Parameters
+    ///
+    /// * `config` - The configuration used to execute blocks
+    ///
+    /// # Returns
+    ///
+    /// A new provider with the configuration's limits applied
+    pub fn from_config(config: AltiusEvmConfig<EvmF, R>) -> Self {
+        let max_transactions = config.max_transactions;
+        Self::new(config).with_max_transactions(max_transactions)
     }
 }
 
@@ -367,6 +1391,832 @@ where
         DB: Database,
     {
         AltiusExecutor::new(self.strategy_factory.clone(), db)
+            .with_max_transactions(self.max_transactions)
+    }
+}
+
+/// A thread-safe handle for spawning executors with a shared configuration.
+///
+/// Created with [`AltiusBlockExecutorProvider::into_shared`]. The strategy factory lives
+/// behind an [`Arc`], so cloning the handle is cheap and every clone can be moved to another
+/// thread. The factory is never mutated through the handle: [`Self::spawn_executor`] only
+/// reads it to clone a private copy for each executor, and the `Send + Sync` bounds on `F`
+/// make concurrent calls safe. State such as the bundle, the gas audit or hotness counts
+/// belongs to each executor and is never shared. The global SSA cache and transaction
+/// manager are process-wide and shared by all executors regardless.
+///
+/// # Examples
+///
+/// ```rust
+/// use reth_evm_altius::{config::AltiusEvmConfig, AltiusBlockExecutorProvider};
+/// use revm::database::EmptyDB;
+///
+/// let shared = AltiusBlockExecutorProvider::new(AltiusEvmConfig::mainnet()).into_shared();
+///
+/// let handle = shared.clone();
+/// std::thread::spawn(move || {
+///     let _executor = handle.spawn_executor(EmptyDB::default());
+/// })
+/// .join()
+/// .unwrap();
+/// ```
+#[derive(Debug)]
+pub struct SharedProvider<F> {
+    /// The strategy factory cloned into every spawned executor.
+    strategy_factory: Arc<F>,
+    /// Maximum number of transactions of a block the spawned executors accept.
+    max_transactions: Option<usize>,
+}
+
+impl<F> Clone for SharedProvider<F> {
+    fn clone(&self) -> Self {
+        Self {
+            strategy_factory: self.strategy_factory.clone(),
+            max_transactions: self.max_transactions,
+        }
+    }
+}
+
+impl<F> SharedProvider<F>
+where
+    F: ConfigureEvm + Send + Sync + 'static,
+{
+    /// Creates an executor for `db` with a copy of the shared strategy factory.
+    ///
+    /// # Parameters
+    ///
+    /// * `db` - The database the executor reads state from
+    ///
+    /// # Returns
+    ///
+    /// A new `AltiusExecutor`, independent of any other executor spawned from this handle
+    pub fn spawn_executor<DB: Database>(&self, db: DB) -> AltiusExecutor<F, DB> {
+        AltiusExecutor::new(F::clone(&self.strategy_factory), db)
+            .with_max_transactions(self.max_transactions)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AltiusEvmConfig;
+    use alloy_consensus::{Header, TxLegacy};
+    use alloy_eips::eip4788::{BEACON_ROOTS_ADDRESS, BEACON_ROOTS_CODE};
+    use alloy_evm::block::{BlockValidationError, StateChangePreBlockSource};
+    use alloy_evm::eth::receipt_builder::{ReceiptBuilder, ReceiptBuilderCtx};
+    use alloy_primitives::{address, bytes, Address, Bytes, Log, TxKind, U256};
+    use reth_chainspec::{ChainSpec, ChainSpecBuilder, MAINNET};
+    use reth_ethereum::evm::RethReceiptBuilder;
+    use reth_ethereum_primitives::{Block, BlockBody, Receipt, Transaction, TransactionSigned};
+    use reth_primitives_traits::{crypto::secp256k1::public_key_to_address, Block as _};
+    use reth_testing_utils::generators::{self, sign_tx_with_key_pair};
+    use revm::{
+        database::{states::AccountInfoRevert, CacheDB, EmptyDB},
+        inspector::Inspector,
+        interpreter::{interpreter::EthInterpreter, CallInputs, CallOutcome},
+        state::{Account, AccountInfo, Bytecode},
+        Database as _,
+    };
+    use secp256k1::Keypair;
+    use std::{
+        ops::Range,
+        sync::{
+            atomic::{AtomicU64, AtomicUsize, Ordering},
+            mpsc, Arc,
+        },
+    };
+
+    pub(crate) const RECIPIENT: Address = address!("0x1000000000000000000000000000000000000000");
+
+    pub(crate) fn chain_spec() -> Arc<ChainSpec> {
+        Arc::new(ChainSpecBuilder::from(&*MAINNET).shanghai_activated().build())
+    }
+
+    /// Returns a database holding a single funded sender, with the sender's key pair.
+    pub(crate) fn funded_sender() -> (CacheDB<EmptyDB>, Keypair, Address) {
+        let sender_key_pair = generators::generate_key(&mut generators::rng());
+        let sender = public_key_to_address(sender_key_pair.public_key());
+
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            sender,
+            AccountInfo { balance: U256::from(1_000_000_000u64), ..Default::default() },
+        );
+
+        (db, sender_key_pair, sender)
+    }
+
+    /// Returns block `number` with transfers of 1 wei to [`RECIPIENT`], one per nonce.
+    pub(crate) fn transfer_block_from(
+        chain_spec: &ChainSpec,
+        sender_key_pair: Keypair,
+        number: u64,
+        nonces: Range<u64>,
+    ) -> RecoveredBlock<Block> {
+        let transactions = nonces
+            .map(|nonce| {
+                sign_tx_with_key_pair(
+                    sender_key_pair,
+                    Transaction::Legacy(TxLegacy {
+                        chain_id: Some(chain_spec.chain.id()),
+                        nonce,
+                        gas_price: 1,
+                        gas_limit: 21_000,
+                        to: TxKind::Call(RECIPIENT),
+                        value: U256::from(1),
+                        input: Bytes::new(),
+                    }),
+                )
+            })
+            .collect();
+
+        let header =
+            Header { number, timestamp: number, gas_limit: 1_000_000, ..Header::default() };
+        Block { header, body: BlockBody { transactions, ..Default::default() } }
+            .try_into_recovered()
+            .unwrap()
+    }
+
+    /// Returns a database with a funded sender and a block with `count` consecutive transfers
+    /// of 1 wei from it to [`RECIPIENT`].
+    pub(crate) fn transfer_block(
+        chain_spec: &ChainSpec,
+        count: u64,
+    ) -> (CacheDB<EmptyDB>, RecoveredBlock<Block>, Address) {
+        let (db, sender_key_pair, sender) = funded_sender();
+        (db, transfer_block_from(chain_spec, sender_key_pair, 1, 0..count), sender)
     }
-} 
 
+    /// A contract flipping storage slot 0 between 0 and 1 on every call.
+    pub(crate) const TOGGLE: Address = address!("0x2000000000000000000000000000000000000000");
+
+    /// Returns block 1 with `count` calls to [`TOGGLE`], with the contract deployed in `db`.
+    ///
+    /// Every second call clears the slot and earns a refund, so the gas of each transaction
+    /// depends on the order in which they execute.
+    pub(crate) fn toggle_block(
+        chain_spec: &ChainSpec,
+        db: &mut CacheDB<EmptyDB>,
+        sender_key_pair: Keypair,
+        count: u64,
+    ) -> RecoveredBlock<Block> {
+        deploy_toggle(db);
+        call_block(chain_spec, sender_key_pair, &vec![TOGGLE; count as usize])
+    }
+
+    /// Deploys the [`TOGGLE`] contract in `db`.
+    pub(crate) fn deploy_toggle(db: &mut CacheDB<EmptyDB>) {
+        // SSTORE(0, ISZERO(SLOAD(0)))
+        let code = Bytecode::new_raw(bytes!("0x6000541560005500"));
+        db.insert_account_info(TOGGLE, AccountInfo { code: Some(code), ..Default::default() });
+    }
+
+    /// Returns block 1 with one call per target, in order, each with 100k gas and no input.
+    pub(crate) fn call_block(
+        chain_spec: &ChainSpec,
+        sender_key_pair: Keypair,
+        targets: &[Address],
+    ) -> RecoveredBlock<Block> {
+        let transactions = targets
+            .iter()
+            .zip(0..)
+            .map(|(target, nonce)| {
+                sign_tx_with_key_pair(
+                    sender_key_pair,
+                    Transaction::Legacy(TxLegacy {
+                        chain_id: Some(chain_spec.chain.id()),
+                        nonce,
+                        gas_price: 1,
+                        gas_limit: 100_000,
+                        to: TxKind::Call(*target),
+                        value: U256::ZERO,
+                        input: Bytes::new(),
+                    }),
+                )
+            })
+            .collect();
+
+        let header = Header { number: 1, timestamp: 1, gas_limit: 1_000_000, ..Header::default() };
+        Block { header, body: BlockBody { transactions, ..Default::default() } }
+            .try_into_recovered()
+            .unwrap()
+    }
+
+    pub(crate) fn executor(
+        chain_spec: Arc<ChainSpec>,
+        db: CacheDB<EmptyDB>,
+    ) -> AltiusExecutor<AltiusEvmConfig, CacheDB<EmptyDB>> {
+        AltiusBlockExecutorProvider::new(AltiusEvmConfig::new(chain_spec)).executor(db)
+    }
+
+    #[test]
+    fn dry_run_leaves_bundle_untouched() {
+        let chain_spec = chain_spec();
+        let (db, block, sender) = transfer_block(&chain_spec, 1);
+        let mut executor = executor(chain_spec, db);
+
+        let diff = executor.dry_run(&block).unwrap();
+
+        // The diff reflects the transfer
+        assert_eq!(
+            diff.account(&RECIPIENT)
+                .and_then(|account| account.info.as_ref())
+                .map(|info| info.balance),
+            Some(U256::from(1))
+        );
+        assert!(diff.account(&sender).is_some());
+
+        // The executor has not committed anything
+        assert!(executor.db.bundle_state.state.is_empty());
+        assert!(executor.db.bundle_state.reverts.is_empty());
+
+        // The same block still executes against the original state
+        let result = executor.execute_one(&block).unwrap();
+        assert_eq!(result.receipts.len(), 1);
+        assert!(result.receipts[0].success);
+    }
+
+    #[test]
+    fn dry_run_keeps_pending_overrides_out_of_the_diff() {
+        let chain_spec = chain_spec();
+        let (_, sender_key_pair, sender) = funded_sender();
+        let block = transfer_block_from(&chain_spec, sender_key_pair, 1, 0..1);
+        let mut executor = executor(chain_spec, CacheDB::new(EmptyDB::default()));
+        let funds = U256::from(1_000_000_000u64);
+        executor
+            .apply_overrides(&StateOverrides::from([(
+                sender,
+                AccountOverride::default().with_balance(funds),
+            )]))
+            .unwrap();
+
+        // The block's changes start from the overridden balance
+        let diff = executor.dry_run(&block).unwrap();
+        let sender_diff = diff.account(&sender).unwrap();
+        assert_eq!(sender_diff.original_info.as_ref().map(|info| info.balance), Some(funds));
+
+        // The override is still pending and lands in the bundle with the executed block
+        executor.execute_one(&block).unwrap();
+        let sender_bundle = executor.db.bundle_state.account(&sender).unwrap();
+        assert_eq!(sender_bundle.original_info, None);
+        assert_eq!(
+            sender_bundle.info.as_ref().map(|info| info.balance),
+            Some(funds - U256::from(21_001))
+        );
+    }
+
+    #[test]
+    fn execute_single_transfer() {
+        let chain_spec = chain_spec();
+        let (db, block, sender) = transfer_block(&chain_spec, 1);
+        let config = AltiusEvmConfig::new(chain_spec.clone());
+        let mut executor = executor(chain_spec, db);
+
+        let tx = block.transactions_recovered().next().unwrap();
+        let result = executor.execute_transaction(config.evm_env(block.header()), tx).unwrap();
+
+        assert!(result.is_success());
+        assert_eq!(result.gas_used(), 21_000);
+
+        let recipient = executor.db.basic(RECIPIENT).unwrap().unwrap();
+        assert_eq!(recipient.balance, U256::from(1));
+        let sender = executor.db.basic(sender).unwrap().unwrap();
+        assert_eq!(sender.nonce, 1);
+    }
+
+    /// Receipt builder that appends a log carrying a running counter to every receipt.
+    #[derive(Debug, Clone, Default)]
+    struct CountingReceiptBuilder {
+        count: Arc<AtomicU64>,
+    }
+
+    impl ReceiptBuilder for CountingReceiptBuilder {
+        type Transaction = TransactionSigned;
+        type Receipt = Receipt;
+
+        fn build_receipt<E: Evm>(
+            &self,
+            ctx: ReceiptBuilderCtx<'_, Self::Transaction, E>,
+        ) -> Self::Receipt {
+            let mut receipt = RethReceiptBuilder::default().build_receipt(ctx);
+            let count = self.count.fetch_add(1, Ordering::Relaxed) + 1;
+            receipt.logs.push(Log::new_unchecked(
+                Address::ZERO,
+                Vec::new(),
+                Bytes::copy_from_slice(&count.to_be_bytes()),
+            ));
+            receipt
+        }
+    }
+
+    #[test]
+    fn custom_receipt_builder_is_used() {
+        let chain_spec = chain_spec();
+        let (db, block, _) = transfer_block(&chain_spec, 1);
+
+        let receipt_builder = CountingReceiptBuilder::default();
+        let config = AltiusEvmConfig::new(chain_spec).with_receipt_builder(receipt_builder.clone());
+        let mut executor = AltiusBlockExecutorProvider::new(config).executor(db);
+
+        let result = executor.execute_one(&block).unwrap();
+
+        assert_eq!(receipt_builder.count.load(Ordering::Relaxed), 1);
+        let tag = result.receipts[0].logs.last().unwrap();
+        assert_eq!(tag.data.data, Bytes::copy_from_slice(&1u64.to_be_bytes()));
+    }
+
+    #[test]
+    fn receipt_callback_sees_every_receipt_of_a_successful_block() {
+        let chain_spec = chain_spec();
+        let (db, block, _) = transfer_block(&chain_spec, 4);
+        let mut executor = executor(chain_spec, db);
+
+        let (tx, rx) = mpsc::channel();
+        let callback_tx = tx.clone();
+        let result = executor
+            .execute_one_with_receipt_callback(&block, move |index, receipt: &Receipt| {
+                let _ = callback_tx.send((index, receipt.cumulative_gas_used));
+            })
+            .unwrap();
+
+        let observed: Vec<_> = rx.try_iter().collect();
+        let expected: Vec<_> = result
+            .receipts
+            .iter()
+            .enumerate()
+            .map(|(index, receipt)| (index, receipt.cumulative_gas_used))
+            .collect();
+        assert_eq!(observed, expected);
+        assert_eq!(observed.iter().map(|(index, _)| *index).collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+
+        // The nonces are used up, so the block fails again and the callback is never called
+        let err = executor.execute_one_with_receipt_callback(&block, move |index, _: &Receipt| {
+            let _ = tx.send((index, 0));
+        });
+        assert!(err.is_err());
+        assert_eq!(rx.try_iter().count(), 0);
+    }
+
+    #[test]
+    fn shared_provider_spawns_executors_concurrently() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<SharedProvider<AltiusEvmConfig>>();
+
+        let chain_spec = chain_spec();
+        let shared = AltiusBlockExecutorProvider::new(AltiusEvmConfig::new(chain_spec.clone()))
+            .into_shared();
+
+        let gas_used: Vec<_> = std::thread::scope(|scope| {
+            let threads: Vec<_> = (1..=4)
+                .map(|count| {
+                    let shared = shared.clone();
+                    let chain_spec = chain_spec.clone();
+                    scope.spawn(move || {
+                        let (db, block, _) = transfer_block(&chain_spec, count);
+                        shared.spawn_executor(db).execute_one(&block).unwrap().gas_used
+                    })
+                })
+                .collect();
+            threads.into_iter().map(|thread| thread.join().unwrap()).collect()
+        });
+
+        assert_eq!(gas_used, [21_000, 42_000, 63_000, 84_000]);
+    }
+
+    /// Inspector counting the calls it observes.
+    #[derive(Debug, Default)]
+    struct CallCounter {
+        calls: usize,
+    }
+
+    impl<CTX> Inspector<CTX, EthInterpreter> for CallCounter {
+        fn call(&mut self, _context: &mut CTX, _inputs: &mut CallInputs) -> Option<CallOutcome> {
+            self.calls += 1;
+            None
+        }
+    }
+
+    #[test]
+    fn inspector_observes_every_transaction() {
+        let chain_spec = chain_spec();
+        let (db, block, _) = transfer_block(&chain_spec, 3);
+        let mut inspected = executor(chain_spec.clone(), db.clone());
+        let mut parallel = executor(chain_spec, db);
+
+        let (result, counter) =
+            inspected.execute_one_with_inspector(&block, CallCounter::default()).unwrap();
+
+        // One top-level call per transfer
+        assert_eq!(counter.calls, 3);
+
+        // The sequential path produces the same outcome as the parallel one
+        let expected = parallel.execute_one(&block).unwrap();
+        assert_eq!(result.receipts, expected.receipts);
+        assert_eq!(result.gas_used, expected.gas_used);
+        assert_eq!(inspected.db.bundle_state, parallel.db.bundle_state);
+    }
+
+    #[test]
+    fn gas_audit_accepts_order_dependent_refunds() {
+        let chain_spec = chain_spec();
+        let (mut db, sender_key_pair, _) = funded_sender();
+        let block = toggle_block(&chain_spec, &mut db, sender_key_pair, 4);
+        let mut audited = executor(chain_spec.clone(), db.clone()).with_gas_audit(true);
+        let mut plain = executor(chain_spec, db);
+
+        let result = audited.execute_one(&block).unwrap();
+        let expected = plain.execute_one(&block).unwrap();
+
+        // Setting the slot and clearing it with a refund alternate
+        let gas: Vec<_> = core::iter::once(0)
+            .chain(result.receipts.iter().map(|receipt| receipt.cumulative_gas_used))
+            .collect::<Vec<_>>()
+            .windows(2)
+            .map(|window| window[1] - window[0])
+            .collect();
+        assert!(gas[1] < gas[0]);
+        assert_eq!(gas[2], gas[0]);
+        assert_eq!(gas[3], gas[1]);
+
+        // The audit leaves the same outcome and state as a plain execution
+        assert_eq!(result.receipts, expected.receipts);
+        assert_eq!(result.gas_used, expected.gas_used);
+        assert_eq!(audited.db.bundle_state, plain.db.bundle_state);
+    }
+
+    #[test]
+    fn gas_mismatch_is_detected() {
+        let result = |cumulative: &[u64]| BlockExecutionResult {
+            receipts: cumulative
+                .iter()
+                .map(|&cumulative_gas_used| Receipt {
+                    cumulative_gas_used,
+                    success: true,
+                    ..Default::default()
+                })
+                .collect::<Vec<_>>(),
+            requests: Default::default(),
+            gas_used: cumulative.last().copied().unwrap_or_default(),
+        };
+
+        assert_eq!(find_gas_mismatch(&result(&[30_000, 50_000]), &result(&[30_000, 50_000])), None);
+        assert_eq!(
+            find_gas_mismatch(&result(&[30_000, 45_000]), &result(&[30_000, 50_000])),
+            Some(GasMismatch::Transaction { tx_index: 1, parallel: 45_000, sequential: 50_000 })
+        );
+
+        let mut parallel = result(&[30_000]);
+        parallel.gas_used = 25_000;
+        assert_eq!(
+            find_gas_mismatch(&parallel, &result(&[30_000])),
+            Some(GasMismatch::Block { parallel: 25_000, sequential: 30_000 })
+        );
+    }
+
+    #[test]
+    fn transfer_access_list() {
+        let chain_spec = chain_spec();
+        let (db, block, sender) = transfer_block(&chain_spec, 2);
+        let mut executor = executor(chain_spec, db);
+
+        let (result, access_list) = executor.execute_one_with_access_list(&block).unwrap();
+        assert_eq!(result.receipts.len(), 2);
+
+        // Both transfers touch the sender and the recipient. The beneficiary receiving their
+        // fees is left out, and plain accounts have no storage slots.
+        let mut expected = vec![sender, RECIPIENT];
+        expected.sort_unstable();
+        assert!(!expected.contains(&block.header().beneficiary));
+        assert_eq!(access_list.0.iter().map(|item| item.address).collect::<Vec<_>>(), expected);
+        assert!(access_list.0.iter().all(|item| item.storage_keys.is_empty()));
+    }
+
+    #[test]
+    fn hotness_counts_transactions_loading_a_contract() {
+        const CALL_TWICE: Address = address!("0x4000000000000000000000000000000000000000");
+        const PROBE: Address = address!("0x5000000000000000000000000000000000000000");
+
+        let chain_spec = chain_spec();
+        let (mut db, sender_key_pair, _) = funded_sender();
+        deploy_toggle(&mut db);
+        // CALL(GAS, TOGGLE, 0, 0, 0, 0, 0) twice
+        let call_toggle =
+            bytes!("0x600060006000600060007320000000000000000000000000000000000000005af150");
+        let call_twice = [call_toggle.as_ref(), call_toggle.as_ref(), &[0x00]].concat();
+        // EXTCODESIZE(TOGGLE) without calling it
+        let probe = bytes!("0x7320000000000000000000000000000000000000003b5000");
+        for (address, code) in [(CALL_TWICE, Bytes::from(call_twice)), (PROBE, probe)] {
+            let code = Some(Bytecode::new_raw(code));
+            db.insert_account_info(address, AccountInfo { code, ..Default::default() });
+        }
+        let block = call_block(&chain_spec, sender_key_pair, &[CALL_TWICE, PROBE]);
+        let mut executor = executor(chain_spec, db).with_hotness_tracking(true);
+
+        executor.execute_one(&block).unwrap();
+
+        // TOGGLE is called twice by the first transaction and only probed by the second, and
+        // counts once for each; the sender has no code and is not counted
+        let code_hash = |executor: &mut AltiusExecutor<_, _>, address| {
+            U256::from_be_bytes(executor.db.basic(address).unwrap().unwrap().code_hash.0)
+        };
+        let toggle = code_hash(&mut executor, TOGGLE);
+        let mut expected =
+            vec![(code_hash(&mut executor, CALL_TWICE), 1), (code_hash(&mut executor, PROBE), 1)];
+        expected.sort_unstable();
+        expected.insert(0, (toggle, 2));
+        assert_eq!(executor.contract_load_counts(), expected);
+
+        executor.reset_hotness();
+        assert!(executor.contract_load_counts().is_empty());
+    }
+
+    #[test]
+    fn composite_hook_forwards_to_every_hook() {
+        let chain_spec = chain_spec();
+        let (db, block, _) = transfer_block(&chain_spec, 3);
+        let mut executor = executor(chain_spec, db);
+
+        let counting_hook = |count: Arc<AtomicUsize>| {
+            move |_: StateChangeSource, _: &EvmState| {
+                count.fetch_add(1, Ordering::Relaxed);
+            }
+        };
+        let first = Arc::new(AtomicUsize::new(0));
+        let second = Arc::new(AtomicUsize::new(0));
+        let hook = CompositeStateHook::default()
+            .with_hook(counting_hook(first.clone()))
+            .with_hook(counting_hook(second.clone()));
+        assert_eq!(hook.len(), 2);
+
+        executor.execute_one_with_state_hook(&block, hook).unwrap();
+
+        // At least one update per transaction, and both hooks see all of them
+        assert!(first.load(Ordering::Relaxed) >= 3);
+        assert_eq!(first.load(Ordering::Relaxed), second.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn block_summary_is_logged() {
+        /// Collects the formatted log output.
+        #[derive(Clone, Default)]
+        struct LogBuffer(Arc<Mutex<Vec<u8>>>);
+
+        impl std::io::Write for LogBuffer {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let chain_spec = chain_spec();
+        let execute_logged = |enabled| {
+            let (db, block, _) = transfer_block(&chain_spec, 2);
+            let mut executor = executor(chain_spec.clone(), db).with_block_summary(enabled);
+
+            let logs = LogBuffer::default();
+            let writer = logs.clone();
+            let subscriber = tracing_subscriber::fmt()
+                .with_writer(move || writer.clone())
+                .with_ansi(false)
+                .finish();
+            tracing::subscriber::with_default(subscriber, || executor.execute_one(&block).unwrap());
+
+            let output = logs.0.lock().unwrap().clone();
+            String::from_utf8(output).unwrap()
+        };
+
+        let logs = execute_logged(true);
+        assert!(logs.contains("Executed block"), "{logs}");
+        for field in ["block_number=1", "tx_count=2", "gas_used=42000", "elapsed_us="] {
+            assert!(logs.contains(field), "{field} missing from {logs}");
+        }
+
+        assert!(!execute_logged(false).contains("Executed block"));
+    }
+
+    #[test]
+    fn external_tx_manager_is_not_reset() {
+        static RESETS: AtomicUsize = AtomicUsize::new(0);
+        fn count_reset() {
+            RESETS.fetch_add(1, Ordering::Relaxed);
+        }
+
+        let chain_spec = chain_spec();
+        let execute = |external| {
+            let (db, block, _) = transfer_block(&chain_spec, 1);
+            let mut executor = executor(chain_spec.clone(), db).with_external_tx_manager(external);
+            executor.reset_tx_manager = count_reset;
+            executor.execute_one(&block).unwrap();
+        };
+
+        execute(false);
+        assert_eq!(RESETS.load(Ordering::Relaxed), 1);
+
+        execute(true);
+        assert_eq!(RESETS.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn blocks_over_the_transaction_limit_fail_early() {
+        let chain_spec = chain_spec();
+        let (db, block, sender) = transfer_block(&chain_spec, 3);
+        let config = AltiusEvmConfig::new(chain_spec);
+        let mut executor =
+            AltiusBlockExecutorProvider::from_config(config.clone().with_max_transactions(Some(2)))
+                .executor(db.clone());
+
+        let err = executor.execute_one(&block).unwrap_err();
+        let expected = PrecheckError::ExceedsConfiguredLimit { count: 3, limit: 2 };
+        assert!(err.to_string().contains(&expected.to_string()), "{err}");
+        assert!(executor.dry_run(&block).is_err());
+
+        // Nothing was executed
+        assert!(executor.db.bundle_state.is_empty());
+        assert!(executor.db.cache.accounts.is_empty());
+        assert_eq!(executor.db.basic(sender).unwrap().unwrap().nonce, 0);
+
+        // Providers created with `new` take the limit from their own setting
+        let provider = AltiusBlockExecutorProvider::new(config.with_max_transactions(Some(2)));
+        assert!(provider.executor(db.clone()).execute_one(&block).is_ok());
+        let shared = provider.with_max_transactions(Some(3)).into_shared();
+        assert_eq!(shared.spawn_executor(db).execute_one(&block).unwrap().receipts.len(), 3);
+    }
+
+    #[test]
+    fn state_clear_prunes_touched_empty_accounts() {
+        const EMPTY: Address = address!("0x3000000000000000000000000000000000000000");
+
+        // Commits a touch of an empty account and returns what the bundle records for it
+        let touch_empty = |config: StateConfig| {
+            let mut executor = AltiusExecutor::new_with_state_config(
+                AltiusEvmConfig::new(chain_spec()),
+                CacheDB::new(EmptyDB::default()),
+                config,
+            );
+            executor.db.basic(EMPTY).unwrap();
+
+            let mut account = Account::default();
+            account.mark_touch();
+            executor.db.commit(EvmState::from_iter([(EMPTY, account)]));
+            executor.db.merge_transitions(BundleRetention::Reverts);
+
+            executor.db.bundle_state.account(&EMPTY).and_then(|account| account.info.clone())
+        };
+
+        assert_eq!(touch_empty(StateConfig::default()), Some(AccountInfo::default()));
+        assert_eq!(touch_empty(StateConfig::default().with_state_clear(true)), None);
+    }
+
+    #[test]
+    fn reverts_restore_pre_block_balances() {
+        let chain_spec = chain_spec();
+        let (db, sender_key_pair, sender) = funded_sender();
+        let mut executor = executor(chain_spec.clone(), db);
+        assert!(executor.reverts_for_last_block().is_empty());
+
+        executor.execute_one(&transfer_block_from(&chain_spec, sender_key_pair, 1, 0..1)).unwrap();
+        let block_2 = transfer_block_from(&chain_spec, sender_key_pair, 2, 1..3);
+        let balances_before: Vec<_> = [sender, RECIPIENT, block_2.header().beneficiary]
+            .into_iter()
+            .map(|address| (address, executor.db.basic(address).unwrap().map(|info| info.balance)))
+            .collect();
+        executor.execute_one(&block_2).unwrap();
+
+        // Only the second block's reverts are returned, and rolling them back restores the
+        // balances from before it
+        let reverts = executor.reverts_for_last_block();
+        assert!(reverts.is_sorted_by_key(|(address, _)| *address));
+        for (address, balance_before) in balances_before {
+            let (_, revert) = reverts.iter().find(|(reverted, _)| *reverted == address).unwrap();
+            let reverted_balance = match &revert.account {
+                AccountInfoRevert::RevertTo(info) => Some(info.balance),
+                AccountInfoRevert::DeleteIt => None,
+                AccountInfoRevert::DoNothing => {
+                    executor.db.basic(address).unwrap().map(|info| info.balance)
+                }
+            };
+            assert_eq!(reverted_balance, balance_before, "{address}");
+        }
+        assert!(serde_json::to_string(&reverts).is_ok());
+
+        // A failing block clears them
+        assert!(executor.execute_one(&block_2).is_err());
+        assert!(executor.reverts_for_last_block().is_empty());
+    }
+
+    #[test]
+    fn beacon_root_system_call_is_reported() {
+        let chain_spec = Arc::new(ChainSpecBuilder::from(&*MAINNET).cancun_activated().build());
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            BEACON_ROOTS_ADDRESS,
+            AccountInfo {
+                nonce: 1,
+                code: Some(Bytecode::new_raw(BEACON_ROOTS_CODE.clone())),
+                ..Default::default()
+            },
+        );
+        let header = Header {
+            number: 1,
+            timestamp: 12,
+            gas_limit: 1_000_000,
+            excess_blob_gas: Some(0),
+            blob_gas_used: Some(0),
+            parent_beacon_block_root: Some(B256::with_last_byte(0x69)),
+            ..Header::default()
+        };
+        let block =
+            RecoveredBlock::new_unhashed(Block { header, body: Default::default() }, vec![]);
+        let mut executor = executor(chain_spec, db);
+
+        let (_, changes) = executor.execute_one_with_system_calls(&block).unwrap();
+
+        // The contract stores the timestamp and the root in its ring buffer
+        let timestamp_slot = U256::from(12 % 8191);
+        let root_slot = timestamp_slot + U256::from(8191);
+        let beacon_root_call = changes
+            .iter()
+            .find(|changes| {
+                matches!(
+                    changes.source,
+                    StateChangeSource::PreBlock(StateChangePreBlockSource::BeaconRootsContract)
+                )
+            })
+            .unwrap();
+        let storage = &beacon_root_call.state[&BEACON_ROOTS_ADDRESS].storage;
+        assert_eq!(storage[&timestamp_slot].present_value, U256::from(12));
+        assert_eq!(storage[&root_slot].present_value, U256::from(0x69));
+
+        assert_eq!(executor.db.storage(BEACON_ROOTS_ADDRESS, root_slot).unwrap(), U256::from(0x69));
+    }
+
+    #[test]
+    fn cancun_block_without_beacon_root_fails_before_execution() {
+        let chain_spec = Arc::new(ChainSpecBuilder::from(&*MAINNET).cancun_activated().build());
+        let (db, block, _) = transfer_block(&chain_spec, 1);
+        assert_eq!(block.header().parent_beacon_block_root, None);
+        let mut executor = executor(chain_spec, db);
+
+        let is_missing_root = |result: Result<_, BlockExecutionError>| {
+            matches!(
+                result,
+                Err(BlockExecutionError::Validation(
+                    BlockValidationError::MissingParentBeaconBlockRoot
+                ))
+            )
+        };
+        assert!(is_missing_root(executor.execute_one(&block).map(drop)));
+        assert!(is_missing_root(executor.dry_run(&block).map(drop)));
+        assert!(executor.db.bundle_state.state.is_empty());
+    }
+
+    #[test]
+    fn clone_state_and_restore() {
+        let chain_spec = chain_spec();
+        let (db, sender_key_pair, _) = funded_sender();
+        let mut executor = executor(chain_spec.clone(), db);
+
+        let block_1 = transfer_block_from(&chain_spec, sender_key_pair, 1, 0..1);
+        executor.execute_one(&block_1).unwrap();
+
+        let snapshot = executor.clone_state();
+        let bundle_at_snapshot = executor.db.bundle_state.clone();
+
+        let block_2 = transfer_block_from(&chain_spec, sender_key_pair, 2, 1..3);
+        executor.execute_one(&block_2).unwrap();
+        assert_eq!(executor.db.basic(RECIPIENT).unwrap().unwrap().balance, U256::from(3));
+
+        executor.restore(snapshot);
+        assert_eq!(executor.db.bundle_state, bundle_at_snapshot);
+        assert_eq!(executor.db.basic(RECIPIENT).unwrap().unwrap().balance, U256::from(1));
+
+        // The second block applies cleanly again on top of the restored state
+        executor.execute_one(&block_2).unwrap();
+        assert_eq!(executor.db.basic(RECIPIENT).unwrap().unwrap().balance, U256::from(3));
+    }
+
+    #[test]
+    fn balance_override_funds_a_transfer() {
+        let chain_spec = chain_spec();
+        let (_, sender_key_pair, sender) = funded_sender();
+        let block = transfer_block_from(&chain_spec, sender_key_pair, 1, 0..1);
+        let mut executor = executor(chain_spec, CacheDB::new(EmptyDB::default()));
+
+        let overrides = StateOverrides::from([(
+            sender,
+            AccountOverride::default().with_balance(U256::from(1_000_000_000u64)),
+        )]);
+        let result = executor.execute_one_with_overrides(&block, overrides).unwrap();
+        assert_eq!(result.receipts.len(), 1);
+        assert!(result.receipts[0].success);
+
+        // The override is gone afterwards, so the sender can no longer pay for the transfer
+        assert_eq!(executor.db.basic(sender).unwrap().unwrap_or_default().balance, U256::ZERO);
+        assert!(executor.execute_one(&block).is_err());
+    }
+}
@@ -0,0 +1,493 @@
+//! Replay of historical blocks through the [`AltiusExecutor`].
+//!
+//! [`replay_range`] reads a range of blocks from a provider, recovers their senders and
+//! executes them in order with a single executor, starting from the state of the parent of
+//! the first block. It is the building block for benchmarking and diffing tools that need
+//! to run real chain data without embedding a full node.
+//!
+//! With [`ReplayOptions::verify_state_root`] set, the state root after every block is
+//! recomputed and compared against the block header, which catches parallel execution bugs
+//! against real chain data at the cost of a trie computation per block.
+//!
+//! [`execute_range_with_coverage`] replays a range with an [`OpcodeCoverage`] inspector to
+//! count the opcodes executed by real chain data.
+//!
+//! [`execute_rlp_block`] executes a single RLP-encoded block against a supplied parent state,
+//! for block fixtures shared as raw RLP by users without a full node.
+//!
+//! Receipt roots are never computed during replay: the executor only returns the receipts, and
+//! their root is derived by consensus validation after execution. Replaying known-good blocks
+//! therefore pays nothing for it, and callers that want to check receipts can compute the root
+//! from the returned receipts themselves.
+
+use crate::{trace::OpcodeCoverage, AltiusExecutor};
+use alloy_evm::FromRecoveredTx;
+use alloy_consensus::BlockHeader;
+use alloy_primitives::{BlockNumber, B256};
+use reth_evm::{
+    execute::{BlockExecutionError, BlockExecutorFactory, Executor},
+    ConfigureEvm, EvmFactory, InspectorFor,
+};
+use reth_execution_types::BlockExecutionResult;
+use reth_primitives_traits::{Block as _, NodePrimitives, RecoveredBlock};
+use reth_revm::database::StateProviderDatabase;
+use reth_storage_api::{
+    errors::provider::ProviderError, BlockReader, StateProvider, StateProviderBox,
+    StateProviderFactory, StateRootProvider,
+};
+use reth_trie_common::{HashedPostState, KeccakKeyHasher};
+use revm::{context::TxEnv, database::State, primitives::hardfork::SpecId};
+
+/// Errors that can occur while replaying a range of blocks.
+///
+/// Every per-block error carries the number of the block that failed.
+#[derive(Debug, thiserror::Error)]
+pub enum ReplayError {
+    /// The genesis block cannot be replayed, it has no parent state to start from.
+    #[error("cannot replay the genesis block")]
+    Genesis,
+    /// The provider does not have the block.
+    #[error("block {0} not found")]
+    MissingBlock(BlockNumber),
+    /// The senders of the block's transactions could not be recovered.
+    #[error("failed to recover the senders of block {0}")]
+    SenderRecovery(BlockNumber),
+    /// The block failed to execute.
+    #[error("failed to execute block {block}: {source}")]
+    Execution {
+        /// The number of the block.
+        block: BlockNumber,
+        /// The execution error.
+        #[source]
+        source: BlockExecutionError,
+    },
+    /// The state root after executing the block differs from the one in its header.
+    #[error("state root mismatch in block {block}: expected {expected}, got {got}")]
+    StateRootMismatch {
+        /// The number of the block.
+        block: BlockNumber,
+        /// The state root in the block header.
+        expected: B256,
+        /// The state root computed after executing the block.
+        got: B256,
+    },
+    /// The provider returned an error.
+    #[error(transparent)]
+    Provider(#[from] ProviderError),
+    /// The bytes are not a valid RLP-encoded block.
+    #[error("failed to decode block: {0}")]
+    Decode(#[source] alloy_rlp::Error),
+}
+
+/// Options for [`replay_range_with_options`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ReplayOptions {
+    /// Recompute the state root after each block and compare it with the block header.
+    ///
+    /// Disabled by default since it requires a trie computation per block.
+    pub verify_state_root: bool,
+}
+
+impl ReplayOptions {
+    /// Enables or disables the state root check after each block.
+    pub const fn with_verify_state_root(mut self, verify_state_root: bool) -> Self {
+        self.verify_state_root = verify_state_root;
+        self
+    }
+}
+
+/// Replays the blocks `from..=to` and returns their execution results in order.
+///
+/// The executor starts from the historical state at block `from - 1` and keeps its state
+/// across blocks, so each block executes on top of the previous one exactly as during sync.
+/// Replay stops at the first block that is missing or fails to execute.
+///
+/// # Parameters
+///
+/// * `provider` - The provider to read blocks and the starting state from
+/// * `config` - The EVM configuration used to execute the blocks
+/// * `from` - The first block to replay, must not be the genesis block
+/// * `to` - The last block to replay, inclusive
+///
+/// # Returns
+///
+/// One `BlockExecutionResult` per block, or the first error encountered
+pub fn replay_range<P, F>(
+    provider: &P,
+    config: F,
+    from: BlockNumber,
+    to: BlockNumber,
+) -> Result<Vec<BlockExecutionResult<<F::Primitives as NodePrimitives>::Receipt>>, ReplayError>
+where
+    P: BlockReader<Block = <F::Primitives as NodePrimitives>::Block> + StateProviderFactory,
+    F: ConfigureEvm,
+    <F::BlockExecutorFactory as BlockExecutorFactory>::EvmFactory: EvmFactory<Tx = TxEnv, Spec = SpecId>,
+    TxEnv: FromRecoveredTx<<<F as ConfigureEvm>::Primitives as NodePrimitives>::SignedTx>,
+{
+    replay_range_with_options(provider, config, from, to, ReplayOptions::default())
+}
+
+/// Replays the blocks `from..=to` like [`replay_range`], with the given options.
+///
+/// When [`ReplayOptions::verify_state_root`] is set, a block whose post-execution state root
+/// differs from its header's `state_root` fails with [`ReplayError::StateRootMismatch`].
+pub fn replay_range_with_options<P, F>(
+    provider: &P,
+    config: F,
+    from: BlockNumber,
+    to: BlockNumber,
+    options: ReplayOptions,
+) -> Result<Vec<BlockExecutionResult<<F::Primitives as NodePrimitives>::Receipt>>, ReplayError>
+where
+    P: BlockReader<Block = <F::Primitives as NodePrimitives>::Block> + StateProviderFactory,
+    F: ConfigureEvm,
+    <F::BlockExecutorFactory as BlockExecutorFactory>::EvmFactory: EvmFactory<Tx = TxEnv, Spec = SpecId>,
+    TxEnv: FromRecoveredTx<<<F as ConfigureEvm>::Primitives as NodePrimitives>::SignedTx>,
+{
+    let parent = from.checked_sub(1).ok_or(ReplayError::Genesis)?;
+    let state = provider.history_by_block_number(parent)?;
+    let mut executor = AltiusExecutor::new(config, StateProviderDatabase::new(state));
+
+    let mut results = Vec::new();
+    for number in from..=to {
+        let block = recovered_block(provider, number)?;
+
+        let result = executor
+            .execute_one(&block)
+            .map_err(|source| ReplayError::Execution { block: number, source })?;
+
+        if options.verify_state_root {
+            // The bundle holds every change since the starting state, so it is applied on
+            // top of the parent state the executor was created with
+            let hashed_state = HashedPostState::from_bundle_state::<KeccakKeyHasher>(
+                &executor.db.bundle_state.state,
+            );
+            let got = executor.db.database.as_ref().state_root(hashed_state)?;
+            let expected = block.header().state_root();
+            if got != expected {
+                return Err(ReplayError::StateRootMismatch { block: number, expected, got })
+            }
+        }
+
+        results.push(result);
+    }
+
+    Ok(results)
+}
+
+/// Replays the blocks `from..=to` with an [`OpcodeCoverage`] inspector attached and returns
+/// how often each opcode was executed across the range.
+///
+/// Inspected blocks execute sequentially (see [`AltiusExecutor::execute_one_with_inspector`]),
+/// so this is slower than [`replay_range`] but produces the same state. Opcodes of system
+/// calls, such as the EIP-4788 beacon root call, are counted along with the transactions'.
+///
+/// # Parameters
+///
+/// * `provider` - The provider to read blocks and the starting state from
+/// * `config` - The EVM configuration used to execute the blocks
+/// * `from` - The first block to replay, must not be the genesis block
+/// * `to` - The last block to replay, inclusive
+///
+/// # Returns
+///
+/// The opcode counts of the whole range, or the first error encountered
+pub fn execute_range_with_coverage<P, F>(
+    provider: &P,
+    config: F,
+    from: BlockNumber,
+    to: BlockNumber,
+) -> Result<OpcodeCoverage, ReplayError>
+where
+    P: BlockReader<Block = <F::Primitives as NodePrimitives>::Block> + StateProviderFactory,
+    F: ConfigureEvm,
+    <F::BlockExecutorFactory as BlockExecutorFactory>::EvmFactory: EvmFactory<Tx = TxEnv, Spec = SpecId>,
+    TxEnv: FromRecoveredTx<<<F as ConfigureEvm>::Primitives as NodePrimitives>::SignedTx>,
+    for<'a> &'a mut OpcodeCoverage:
+        InspectorFor<F, &'a mut State<StateProviderDatabase<StateProviderBox>>>,
+{
+    let parent = from.checked_sub(1).ok_or(ReplayError::Genesis)?;
+    let state = provider.history_by_block_number(parent)?;
+    let mut executor = AltiusExecutor::new(config, StateProviderDatabase::new(state));
+
+    let mut coverage = OpcodeCoverage::default();
+    for number in from..=to {
+        let block = recovered_block(provider, number)?;
+        (_, coverage) = executor
+            .execute_one_with_inspector(&block, coverage)
+            .map_err(|source| ReplayError::Execution { block: number, source })?;
+    }
+
+    Ok(coverage)
+}
+
+/// Reads block `number` from the provider and recovers its senders.
+fn recovered_block<P>(
+    provider: &P,
+    number: BlockNumber,
+) -> Result<RecoveredBlock<P::Block>, ReplayError>
+where
+    P: BlockReader,
+{
+    provider
+        .block_by_number(number)?
+        .ok_or(ReplayError::MissingBlock(number))?
+        .try_into_recovered()
+        .map_err(|_| ReplayError::SenderRecovery(number))
+}
+
+/// Decodes an RLP-encoded block and executes it on top of `parent_state`.
+///
+/// The block is executed exactly like during replay, with a fresh executor whose state starts
+/// from `parent_state`, e.g. the state of the block's parent from a provider or a fixture.
+///
+/// # Parameters
+///
+/// * `config` - The EVM configuration used to execute the block
+/// * `rlp` - The RLP encoding of the block, with no trailing bytes
+/// * `parent_state` - The state the block executes on
+///
+/// # Returns
+///
+/// The execution result of the block, or [`ReplayError::Decode`] if `rlp` is not a block
+pub fn execute_rlp_block<F, S>(
+    config: F,
+    rlp: &[u8],
+    parent_state: S,
+) -> Result<BlockExecutionResult<<F::Primitives as NodePrimitives>::Receipt>, ReplayError>
+where
+    F: ConfigureEvm,
+    <F::BlockExecutorFactory as BlockExecutorFactory>::EvmFactory: EvmFactory<Tx = TxEnv, Spec = SpecId>,
+    TxEnv: FromRecoveredTx<<<F as ConfigureEvm>::Primitives as NodePrimitives>::SignedTx>,
+    S: StateProvider,
+{
+    let block: <F::Primitives as NodePrimitives>::Block =
+        alloy_rlp::decode_exact(rlp).map_err(ReplayError::Decode)?;
+    let number = block.header().number();
+    let block = block.try_into_recovered().map_err(|_| ReplayError::SenderRecovery(number))?;
+
+    let mut executor = AltiusExecutor::new(config, StateProviderDatabase::new(parent_state));
+    executor.execute_one(&block).map_err(|source| ReplayError::Execution { block: number, source })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::AltiusEvmConfig,
+        tests::{chain_spec, funded_sender, toggle_block, transfer_block_from, TOGGLE},
+    };
+    use alloy_genesis::{Genesis, GenesisAccount};
+    use alloy_primitives::U256;
+    use reth_chainspec::{ChainSpec, ChainSpecBuilder, MAINNET};
+    use reth_db_common::init::init_genesis;
+    use reth_evm::execute::BlockExecutorProvider;
+    use reth_evm_ethereum::execute::EthExecutorProvider;
+    use reth_primitives_traits::{crypto::secp256k1::public_key_to_address, Block as _};
+    use reth_provider::{
+        test_utils::{
+            create_test_provider_factory_with_chain_spec, ExtendedAccount, MockEthProvider,
+            MockNodeTypesWithDB,
+        },
+        BlockWriter, DatabaseProviderFactory, ProviderFactory, StorageLocation,
+    };
+    use reth_testing_utils::generators;
+    use std::sync::Arc;
+
+    /// Returns a provider holding a funded sender and blocks `1..=blocks`, each with
+    /// `transfers` transfers from that sender.
+    fn provider_with_blocks(blocks: u64, transfers: u64) -> MockEthProvider {
+        let chain_spec = chain_spec();
+        let sender_key_pair = generators::generate_key(&mut generators::rng());
+        let sender = public_key_to_address(sender_key_pair.public_key());
+
+        let provider = MockEthProvider::default();
+        provider.add_account(sender, ExtendedAccount::new(0, U256::from(1_000_000_000u64)));
+        for number in 1..=blocks {
+            let nonces = (number - 1) * transfers..number * transfers;
+            let block = transfer_block_from(&chain_spec, sender_key_pair, number, nonces);
+            provider.add_block(block.hash(), block.into_block());
+        }
+
+        provider
+    }
+
+    #[test]
+    fn replays_blocks_in_order() {
+        let provider = provider_with_blocks(3, 2);
+
+        let results = replay_range(&provider, AltiusEvmConfig::new(chain_spec()), 1, 3).unwrap();
+
+        // Later blocks only succeed if the nonces of the earlier ones were applied
+        assert_eq!(results.len(), 3);
+        for result in &results {
+            assert_eq!(result.receipts.len(), 2);
+            assert!(result.receipts.iter().all(|receipt| receipt.success));
+        }
+        assert_eq!(results[2].receipts[1].cumulative_gas_used, 42_000);
+    }
+
+    /// Returns a database-backed provider whose genesis funds a sender, holding blocks
+    /// `1..=blocks` of one transfer each, together with the chain spec and the blocks' state
+    /// roots.
+    ///
+    /// The roots are computed from the post-state of reth's sequential Ethereum executor and
+    /// written to the headers, except for block `wrong_root_at` whose header gets a bogus root.
+    fn provider_with_state_roots(
+        blocks: u64,
+        wrong_root_at: Option<BlockNumber>,
+    ) -> (ProviderFactory<MockNodeTypesWithDB>, Arc<ChainSpec>, Vec<B256>) {
+        let sender_key_pair = generators::generate_key(&mut generators::rng());
+        let sender = public_key_to_address(sender_key_pair.public_key());
+        let genesis = Genesis::default().extend_accounts([(
+            sender,
+            GenesisAccount::default().with_balance(U256::from(1_000_000_000u64)),
+        )]);
+        let chain_spec = Arc::new(
+            ChainSpecBuilder::from(&*MAINNET).genesis(genesis).shanghai_activated().build(),
+        );
+        let factory = create_test_provider_factory_with_chain_spec(chain_spec.clone());
+        init_genesis(&factory).unwrap();
+
+        let blocks: Vec<_> = (1..=blocks)
+            .map(|number| {
+                transfer_block_from(&chain_spec, sender_key_pair, number, number - 1..number)
+            })
+            .collect();
+
+        let provider_rw = factory.database_provider_rw().unwrap();
+        let mut state_roots = Vec::with_capacity(blocks.len());
+        for (index, block) in blocks.iter().enumerate() {
+            let outcome = EthExecutorProvider::ethereum(chain_spec.clone())
+                .executor(StateProviderDatabase::new(factory.latest().unwrap()))
+                .execute_batch(&blocks[..=index])
+                .unwrap();
+            let hashed_state =
+                HashedPostState::from_bundle_state::<KeccakKeyHasher>(&outcome.bundle.state);
+            let state_root = factory.latest().unwrap().state_root(hashed_state).unwrap();
+            state_roots.push(state_root);
+
+            let senders = block.senders().to_vec();
+            let mut block = block.clone().into_block();
+            block.header.state_root = if wrong_root_at == Some(block.header.number) {
+                B256::repeat_byte(0x11)
+            } else {
+                state_root
+            };
+            let block = RecoveredBlock::new_unhashed(block, senders);
+            provider_rw.insert_block(block, StorageLocation::Database).unwrap();
+        }
+        provider_rw.commit().unwrap();
+
+        (factory, chain_spec, state_roots)
+    }
+
+    #[test]
+    fn state_roots_of_known_good_blocks_match() {
+        let (provider, chain_spec, _) = provider_with_state_roots(3, None);
+        let options = ReplayOptions::default().with_verify_state_root(true);
+
+        let results =
+            replay_range_with_options(&provider, AltiusEvmConfig::new(chain_spec), 1, 3, options)
+                .unwrap();
+
+        assert_eq!(results.len(), 3);
+    }
+
+    #[test]
+    fn state_root_mismatch_is_reported() {
+        let (provider, chain_spec, state_roots) = provider_with_state_roots(3, Some(2));
+        let options = ReplayOptions::default().with_verify_state_root(true);
+
+        let err = replay_range_with_options(
+            &provider,
+            AltiusEvmConfig::new(chain_spec.clone()),
+            1,
+            3,
+            options,
+        )
+        .unwrap_err();
+
+        assert!(matches!(
+            err,
+            ReplayError::StateRootMismatch { block: 2, expected, got }
+                if expected == B256::repeat_byte(0x11) && got == state_roots[1]
+        ));
+
+        // Without the check the same blocks replay fine
+        assert!(replay_range(&provider, AltiusEvmConfig::new(chain_spec), 1, 3).is_ok());
+    }
+
+    #[test]
+    fn missing_block_reports_its_number() {
+        let provider = provider_with_blocks(2, 1);
+
+        let err = replay_range(&provider, AltiusEvmConfig::new(chain_spec()), 1, 4).unwrap_err();
+
+        assert!(matches!(err, ReplayError::MissingBlock(3)));
+    }
+
+    #[test]
+    fn range_past_the_tip_reports_the_first_missing_block() {
+        let provider = provider_with_blocks(2, 1);
+
+        let err =
+            replay_range(&provider, AltiusEvmConfig::new(chain_spec()), 1, u64::MAX).unwrap_err();
+
+        assert!(matches!(err, ReplayError::MissingBlock(3)));
+    }
+
+    #[test]
+    fn executes_rlp_block() {
+        let chain_spec = chain_spec();
+        let (_, sender_key_pair, sender) = funded_sender();
+        let provider = MockEthProvider::default();
+        provider.add_account(sender, ExtendedAccount::new(0, U256::from(1_000_000_000u64)));
+        let block = transfer_block_from(&chain_spec, sender_key_pair, 1, 0..3);
+        let rlp = alloy_rlp::encode(block.into_block());
+
+        let result = execute_rlp_block(
+            AltiusEvmConfig::new(chain_spec.clone()),
+            &rlp,
+            provider.latest().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(result.receipts.len(), 3);
+
+        let err = execute_rlp_block(
+            AltiusEvmConfig::new(chain_spec),
+            &rlp[..rlp.len() - 1],
+            provider.latest().unwrap(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ReplayError::Decode(_)));
+    }
+
+    #[test]
+    fn coverage_counts_executed_opcodes() {
+        let chain_spec = chain_spec();
+        let (mut db, sender_key_pair, sender) = funded_sender();
+        let block = toggle_block(&chain_spec, &mut db, sender_key_pair, 2);
+
+        let provider = MockEthProvider::default();
+        provider.add_account(sender, ExtendedAccount::new(0, U256::from(1_000_000_000u64)));
+        let code = db.cache.accounts[&TOGGLE].info.code.clone().unwrap().original_bytes();
+        provider.add_account(TOGGLE, ExtendedAccount::new(0, U256::ZERO).with_bytecode(code));
+        provider.add_block(block.hash(), block.into_block());
+
+        let coverage =
+            execute_range_with_coverage(&provider, AltiusEvmConfig::new(chain_spec), 1, 1).unwrap();
+
+        // Each call runs PUSH1 SLOAD ISZERO PUSH1 SSTORE STOP
+        assert_eq!(coverage.frequencies(), [(0x60, 4), (0x00, 2), (0x15, 2), (0x54, 2), (0x55, 2)]);
+    }
+
+    #[test]
+    fn genesis_is_rejected() {
+        let provider = provider_with_blocks(1, 1);
+
+        let err = replay_range(&provider, AltiusEvmConfig::new(chain_spec()), 0, 1).unwrap_err();
+
+        assert!(matches!(err, ReplayError::Genesis));
+    }
+}
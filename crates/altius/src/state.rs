@@ -0,0 +1,135 @@
+//! Standalone files holding a [`BundleState`].
+//!
+//! [`export_bundle`] writes the state changes collected by an executor, e.g. the bundle of
+//! `Executor::into_state`, to a file that [`import_bundle`] reads back. Backtesting tools use
+//! this to capture the outcome of a run and diff it later without keeping the node's database
+//! around.
+//!
+//! The file is JSON with a `version` field next to the bundle. Files written by a newer,
+//! incompatible version are rejected with [`BundleFileError::UnsupportedVersion`] instead of
+//! being misread.
+
+use revm::database::BundleState;
+use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, Write},
+    path::Path,
+};
+
+/// The version written by [`export_bundle`].
+pub const BUNDLE_FILE_VERSION: u32 = 1;
+
+/// Errors that can occur while exporting or importing a bundle file.
+#[derive(Debug, thiserror::Error)]
+pub enum BundleFileError {
+    /// The file could not be read or written.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    /// The file is not a valid bundle file.
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    /// The file was written with a format version this build does not understand.
+    #[error("unsupported bundle file version {0}, expected {BUNDLE_FILE_VERSION}")]
+    UnsupportedVersion(u32),
+}
+
+/// The contents of a bundle file.
+///
+/// On import, the bundle is first read as a [`RawValue`], so the version is checked before the
+/// bundle itself is parsed.
+#[derive(Serialize, Deserialize)]
+struct BundleFile<T> {
+    version: u32,
+    bundle: T,
+}
+
+/// Writes `state` to a bundle file at `path`, replacing any existing file.
+///
+/// Accounts, storage, reverts and contract bytecode are all preserved.
+///
+/// # Parameters
+///
+/// * `state` - The bundle to export
+/// * `path` - The file to write
+pub fn export_bundle(state: &BundleState, path: &Path) -> Result<(), BundleFileError> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    serde_json::to_writer(
+        &mut writer,
+        &BundleFile { version: BUNDLE_FILE_VERSION, bundle: state },
+    )?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Reads a bundle file written by [`export_bundle`].
+///
+/// The file's version is checked before the bundle is parsed. Parsing works on the bundle's
+/// JSON text, which is held in memory until the bundle is built.
+///
+/// # Parameters
+///
+/// * `path` - The file to read
+///
+/// # Returns
+///
+/// The bundle, equal to the one that was exported
+pub fn import_bundle(path: &Path) -> Result<BundleState, BundleFileError> {
+    let file: BundleFile<Box<RawValue>> =
+        serde_json::from_reader(BufReader::new(File::open(path)?))?;
+    if file.version != BUNDLE_FILE_VERSION {
+        return Err(BundleFileError::UnsupportedVersion(file.version))
+    }
+
+    Ok(serde_json::from_str(file.bundle.get())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{chain_spec, executor, funded_sender, toggle_block, TOGGLE};
+    use alloy_primitives::{bytes, U256};
+    use reth_evm::execute::Executor;
+    use revm::{state::Bytecode, Database as _};
+
+    #[test]
+    fn bundle_round_trips() {
+        let chain_spec = chain_spec();
+        let (mut db, sender_key_pair, _) = funded_sender();
+        let block = toggle_block(&chain_spec, &mut db, sender_key_pair, 3);
+        let mut executor = executor(chain_spec, db);
+        executor.execute_one(&block).unwrap();
+        let code_hash = executor.db.basic(TOGGLE).unwrap().unwrap().code_hash;
+
+        let mut bundle = executor.into_state().take_bundle();
+        bundle.contracts.insert(code_hash, Bytecode::new_raw(bytes!("0x6000541560005500")));
+
+        // The block changed the sender, the contract's storage and recorded reverts
+        assert!(bundle.state.len() >= 2);
+        assert_eq!(
+            bundle.account(&TOGGLE).and_then(|account| account.storage_slot(U256::ZERO)),
+            Some(U256::from(1))
+        );
+        assert!(!bundle.reverts.is_empty());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bundle.json");
+        export_bundle(&bundle, &path).unwrap();
+
+        assert_eq!(import_bundle(&path).unwrap(), bundle);
+    }
+
+    #[test]
+    fn unknown_version_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bundle.json");
+        std::fs::write(&path, r#"{"version":2,"bundle":{}}"#).unwrap();
+
+        assert!(matches!(import_bundle(&path), Err(BundleFileError::UnsupportedVersion(2))));
+        assert!(matches!(
+            import_bundle(&dir.path().join("missing.json")),
+            Err(BundleFileError::Io(_))
+        ));
+    }
+}
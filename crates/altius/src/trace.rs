@@ -0,0 +1,297 @@
+//! Opcode-level traces for checking the Altius EVM against geth.
+//!
+//! [`StepRecorder`] records every opcode a transaction executes in the shape of geth's struct
+//! logger. Attach it with
+//! [`AltiusExecutor::execute_transaction_with_inspector`](crate::AltiusExecutor::execute_transaction_with_inspector)
+//! and compare the recorded steps with a `debug_traceTransaction` result loaded through
+//! [`GethTrace::from_json`] using [`compare_traces`]. A divergence usually points at a
+//! difference in EVM configuration, e.g. a hardfork or gas schedule mismatch.
+//!
+//! [`OpcodeCoverage`] counts how often each opcode is executed instead of recording every
+//! step, to find out which opcodes matter most for SSA support. See
+//! [`execute_range_with_coverage`](crate::replay::execute_range_with_coverage) for collecting
+//! it over a range of historical blocks.
+
+use alloy_primitives::U256;
+use core::fmt;
+use revm::{
+    bytecode::opcode::OpCode,
+    inspector::Inspector,
+    interpreter::{
+        interpreter::EthInterpreter,
+        interpreter_types::{Jumps, LoopControl},
+        Interpreter,
+    },
+};
+use serde::Deserialize;
+
+/// The result of geth's `debug_traceTransaction` with the default struct logger.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GethTrace {
+    /// Gas used by the transaction, after refunds.
+    pub gas: u64,
+    /// Whether the transaction reverted or halted.
+    pub failed: bool,
+    /// One entry per executed opcode, across all call frames.
+    pub struct_logs: Vec<TraceStep>,
+}
+
+impl GethTrace {
+    /// Parses the JSON result of `debug_traceTransaction`.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+/// One executed opcode, with the fields traces are compared on.
+///
+/// The other fields of geth's struct logs, such as `gasCost`, `depth` and `memory`, are
+/// ignored.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct TraceStep {
+    /// Program counter of the opcode.
+    pub pc: u64,
+    /// Name of the opcode, e.g. `SLOAD`.
+    pub op: String,
+    /// Gas remaining before the opcode executes.
+    pub gas: u64,
+    /// The stack before the opcode executes, bottom first.
+    #[serde(default)]
+    pub stack: Vec<U256>,
+}
+
+impl fmt::Display for TraceStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at pc {} with {} gas, stack [", self.op, self.pc, self.gas)?;
+        for (index, value) in self.stack.iter().enumerate() {
+            if index > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{value:#x}")?;
+        }
+        f.write_str("]")
+    }
+}
+
+/// An inspector recording every executed opcode as a [`TraceStep`].
+#[derive(Debug, Default)]
+pub struct StepRecorder {
+    steps: Vec<TraceStep>,
+}
+
+impl StepRecorder {
+    /// Returns the steps recorded so far, in execution order.
+    pub fn into_steps(self) -> Vec<TraceStep> {
+        self.steps
+    }
+}
+
+impl<CTX> Inspector<CTX, EthInterpreter> for StepRecorder {
+    fn step(&mut self, interp: &mut Interpreter<EthInterpreter>, _context: &mut CTX) {
+        let opcode = interp.bytecode.opcode();
+        // Unknown opcodes are named the way geth names them
+        let op = OpCode::new(opcode).map_or_else(
+            || format!("opcode {opcode:#x} not defined"),
+            |op| op.as_str().to_string(),
+        );
+
+        self.steps.push(TraceStep {
+            pc: interp.bytecode.pc() as u64,
+            op,
+            gas: interp.control.gas().remaining(),
+            stack: interp.stack.data().clone(),
+        });
+    }
+}
+
+/// An inspector counting how often each opcode is executed.
+///
+/// Counts accumulate across every transaction and block the inspector is attached to, so the
+/// same collector can be passed from one block to the next.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpcodeCoverage {
+    counts: [u64; 256],
+}
+
+impl Default for OpcodeCoverage {
+    fn default() -> Self {
+        Self { counts: [0; 256] }
+    }
+}
+
+impl OpcodeCoverage {
+    /// Returns how often `opcode` was executed.
+    pub fn count(&self, opcode: u8) -> u64 {
+        self.counts[opcode as usize]
+    }
+
+    /// Returns the total number of executed opcodes.
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Returns the executed opcodes with their counts, most frequent first. Opcodes with the
+    /// same count are ordered by value, and opcodes never executed are left out.
+    pub fn frequencies(&self) -> Vec<(u8, u64)> {
+        let mut frequencies: Vec<_> = (0..=u8::MAX)
+            .map(|opcode| (opcode, self.count(opcode)))
+            .filter(|(_, count)| *count > 0)
+            .collect();
+        frequencies.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        frequencies
+    }
+}
+
+/// Prints the frequency table, one opcode per line with its count and share of the total.
+impl fmt::Display for OpcodeCoverage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total = self.total().max(1) as f64;
+        for (opcode, count) in self.frequencies() {
+            let name = OpCode::new(opcode).map_or("UNKNOWN", |op| op.as_str());
+            let share = count as f64 / total * 100.0;
+            writeln!(f, "{name:<14} {opcode:#04x} {count:>12} {share:>6.2}%")?;
+        }
+        Ok(())
+    }
+}
+
+impl<CTX> Inspector<CTX, EthInterpreter> for OpcodeCoverage {
+    fn step(&mut self, interp: &mut Interpreter<EthInterpreter>, _context: &mut CTX) {
+        self.counts[interp.bytecode.opcode() as usize] += 1;
+    }
+}
+
+/// The first step at which two traces differ, returned by [`compare_traces`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceDivergence {
+    /// Index of the first differing step.
+    pub index: usize,
+    /// The expected step, `None` if the actual trace has extra steps.
+    pub expected: Option<TraceStep>,
+    /// The actual step, `None` if the actual trace ends early.
+    pub actual: Option<TraceStep>,
+    /// The last step both traces agree on, `None` if they differ from the first step.
+    pub previous: Option<TraceStep>,
+}
+
+impl fmt::Display for TraceDivergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "traces diverge at step {}: expected ", self.index)?;
+        match &self.expected {
+            Some(step) => write!(f, "{step}")?,
+            None => f.write_str("the end of the trace")?,
+        }
+        f.write_str(", got ")?;
+        match &self.actual {
+            Some(step) => write!(f, "{step}")?,
+            None => f.write_str("the end of the trace")?,
+        }
+        if let Some(previous) = &self.previous {
+            write!(f, " (after {previous})")?;
+        }
+        Ok(())
+    }
+}
+
+impl core::error::Error for TraceDivergence {}
+
+/// Compares a recorded trace with the expected one, step by step.
+///
+/// # Parameters
+///
+/// * `expected` - The reference steps, e.g. [`GethTrace::struct_logs`]
+/// * `actual` - The steps recorded by a [`StepRecorder`]
+///
+/// # Returns
+///
+/// `Ok(())` if both traces are identical, otherwise the first divergent step with the step
+/// before it for context
+pub fn compare_traces(expected: &[TraceStep], actual: &[TraceStep]) -> Result<(), TraceDivergence> {
+    let index = expected
+        .iter()
+        .zip(actual)
+        .position(|(expected, actual)| expected != actual)
+        .unwrap_or_else(|| expected.len().min(actual.len()));
+    if index == expected.len() && index == actual.len() {
+        return Ok(())
+    }
+
+    Err(TraceDivergence {
+        index,
+        expected: expected.get(index).cloned(),
+        actual: actual.get(index).cloned(),
+        previous: index.checked_sub(1).map(|previous| expected[previous].clone()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{chain_spec, executor, funded_sender, toggle_block};
+    use reth_evm::ConfigureEvm;
+
+    // Traces of the two calls of a toggle block in the struct logger format: the first call
+    // sets the slot, the second clears it and earns a refund. The gas values follow the
+    // Shanghai schedule.
+    const TOGGLE_SET: &str = include_str!("../testdata/geth/toggle_set.json");
+    const TOGGLE_CLEAR: &str = include_str!("../testdata/geth/toggle_clear.json");
+
+    #[test]
+    fn toggle_calls_match_geth_traces() {
+        let chain_spec = chain_spec();
+        let (mut db, sender_key_pair, _) = funded_sender();
+        let block = toggle_block(&chain_spec, &mut db, sender_key_pair, 2);
+        let mut executor = executor(chain_spec, db);
+        let evm_env = executor.strategy_factory.evm_env(block.header());
+
+        for (tx, fixture) in block.transactions_recovered().zip([TOGGLE_SET, TOGGLE_CLEAR]) {
+            let expected = GethTrace::from_json(fixture).unwrap();
+
+            let (result, recorder) = executor
+                .execute_transaction_with_inspector(evm_env.clone(), tx, StepRecorder::default())
+                .unwrap();
+
+            let steps = recorder.into_steps();
+            if let Err(divergence) = compare_traces(&expected.struct_logs, &steps) {
+                panic!("{divergence}");
+            }
+            assert_eq!(result.gas_used(), expected.gas);
+            assert_eq!(!result.is_success(), expected.failed);
+        }
+    }
+
+    #[test]
+    fn first_divergent_step_is_reported() {
+        let expected = GethTrace::from_json(TOGGLE_SET).unwrap().struct_logs;
+        assert_eq!(compare_traces(&expected, &expected), Ok(()));
+
+        let mut actual = expected.clone();
+        actual[4].gas += 100;
+        let divergence = compare_traces(&expected, &actual).unwrap_err();
+        assert_eq!(divergence.index, 4);
+        assert_eq!(divergence.previous.as_ref(), Some(&expected[3]));
+        assert_eq!(
+            divergence.to_string(),
+            "traces diverge at step 4: expected SSTORE at pc 6 with 76891 gas, stack [0x1, 0x0], \
+             got SSTORE at pc 6 with 76991 gas, stack [0x1, 0x0] \
+             (after PUSH1 at pc 4 with 76894 gas, stack [0x1])"
+        );
+
+        // A trace ending early diverges where it ends
+        let divergence = compare_traces(&expected, &expected[..5]).unwrap_err();
+        assert_eq!((divergence.index, divergence.actual), (5, None));
+    }
+
+    #[test]
+    fn coverage_table_is_sorted() {
+        let mut coverage = OpcodeCoverage::default();
+        for opcode in [0x60, 0x54, 0x60, 0x55, 0x00, 0x60] {
+            coverage.counts[opcode] += 1;
+        }
+
+        assert_eq!(coverage.total(), 6);
+        assert_eq!(coverage.frequencies(), [(0x60, 3), (0x00, 1), (0x54, 1), (0x55, 1)]);
+        assert!(coverage.to_string().starts_with("PUSH1          0x60            3  50.00%\n"));
+    }
+}
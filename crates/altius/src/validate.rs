@@ -0,0 +1,220 @@
+//! Cheap sanity checks run before a block is handed to the executor.
+//!
+//! The executor assumes a well-formed [`RecoveredBlock`]. A block that breaks one of these
+//! invariants otherwise fails somewhere deep inside the EVM or the parallel scheduler, with an
+//! error that is hard to trace back to its cause. [`precheck_block`] reports it up front.
+//!
+//! The checks only look at the block itself, they do not replace consensus validation.
+//! [`precheck_beacon_root`] additionally needs the hardfork the block executes under, to know
+//! whether the block falls in the Cancun era, and reports the same error as the EIP-4788 system
+//! call would.
+
+use alloy_consensus::{BlockHeader, Transaction};
+use alloy_evm::block::BlockValidationError;
+use reth_evm::execute::BlockExecutionError;
+use reth_primitives_traits::{Block, BlockBody, RecoveredBlock};
+use revm::primitives::hardfork::SpecId;
+
+/// The intrinsic gas of the cheapest possible transaction, a plain transfer.
+const MIN_TRANSACTION_GAS: u64 = 21_000;
+
+/// Errors reported by [`precheck_block`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PrecheckError {
+    /// The number of recovered senders does not match the number of transactions.
+    #[error("block has {transactions} transactions but {senders} recovered senders")]
+    MissingSenders {
+        /// The number of transactions in the block.
+        transactions: usize,
+        /// The number of recovered senders.
+        senders: usize,
+    },
+    /// The header claims more gas used than its gas limit allows.
+    #[error("block gas used {gas_used} exceeds the block gas limit {gas_limit}")]
+    GasUsedExceedsLimit {
+        /// The gas used in the header.
+        gas_used: u64,
+        /// The gas limit in the header.
+        gas_limit: u64,
+    },
+    /// A transaction's gas limit is above the block gas limit.
+    #[error(
+        "transaction {tx_index} has gas limit {gas_limit}, above the block gas limit {block_gas_limit}"
+    )]
+    TransactionGasExceedsLimit {
+        /// The index of the transaction in the block.
+        tx_index: usize,
+        /// The gas limit of the transaction.
+        gas_limit: u64,
+        /// The gas limit of the block.
+        block_gas_limit: u64,
+    },
+    /// The block holds more transactions than its gas limit can pay for.
+    #[error("block has {count} transactions, at most {max} fit in its gas limit")]
+    ExceedsGasCapacity {
+        /// The number of transactions in the block.
+        count: usize,
+        /// The maximum number of transactions the block's gas limit allows.
+        max: u64,
+    },
+    /// The block holds more transactions than the executor's configuration accepts.
+    #[error("block has {count} transactions, above the configured limit of {limit}")]
+    ExceedsConfiguredLimit {
+        /// The number of transactions in the block.
+        count: usize,
+        /// The configured maximum number of transactions.
+        limit: usize,
+    },
+}
+
+/// Checks that `block` is well-formed enough to be executed.
+///
+/// This verifies that every transaction has a recovered sender, that the header's gas used and
+/// each transaction's gas limit fit in the block gas limit, and that the block does not hold
+/// more transactions than its gas limit can pay for at 21000 gas each. An empty block is valid.
+///
+/// # Parameters
+///
+/// * `block` - The block about to be executed
+///
+/// # Returns
+///
+/// `Ok(())` if the block can be executed, or the first problem found
+pub fn precheck_block<B: Block>(block: &RecoveredBlock<B>) -> Result<(), PrecheckError> {
+    let transactions = block.body().transactions();
+    if block.senders().len() != transactions.len() {
+        return Err(PrecheckError::MissingSenders {
+            transactions: transactions.len(),
+            senders: block.senders().len(),
+        })
+    }
+
+    let header = block.header();
+    let block_gas_limit = header.gas_limit();
+    if header.gas_used() > block_gas_limit {
+        return Err(PrecheckError::GasUsedExceedsLimit {
+            gas_used: header.gas_used(),
+            gas_limit: block_gas_limit,
+        })
+    }
+
+    if let Some((tx_index, tx)) =
+        transactions.iter().enumerate().find(|(_, tx)| tx.gas_limit() > block_gas_limit)
+    {
+        return Err(PrecheckError::TransactionGasExceedsLimit {
+            tx_index,
+            gas_limit: tx.gas_limit(),
+            block_gas_limit,
+        })
+    }
+
+    let max = block_gas_limit / MIN_TRANSACTION_GAS;
+    if transactions.len() as u64 > max {
+        return Err(PrecheckError::ExceedsGasCapacity { count: transactions.len(), max })
+    }
+
+    Ok(())
+}
+
+/// Checks that a Cancun-era block carries the parent beacon block root.
+///
+/// The root is passed to the EIP-4788 system call at the start of the block. The executor
+/// runs this check before any work is dispatched, so a block without it fails up front instead
+/// of from inside the parallel scheduler. Blocks before Cancun are not checked.
+///
+/// # Parameters
+///
+/// * `spec` - The hardfork the block executes under, e.g. from
+///   `AltiusEvmConfig::spec_for_header`
+/// * `header` - The header of the block about to be executed
+///
+/// # Returns
+///
+/// `Ok(())` if the block has a root or does not need one, otherwise
+/// `BlockValidationError::MissingParentBeaconBlockRoot`
+pub fn precheck_beacon_root<H: BlockHeader>(
+    spec: SpecId,
+    header: &H,
+) -> Result<(), BlockExecutionError> {
+    if spec.is_enabled_in(SpecId::CANCUN) && header.parent_beacon_block_root().is_none() {
+        return Err(BlockValidationError::MissingParentBeaconBlockRoot.into())
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{chain_spec, funded_sender, transfer_block_from};
+    use alloy_primitives::B256;
+    use reth_ethereum_primitives::Block as EthBlock;
+
+    /// Returns a block with `count` transfers and the given header gas limit.
+    fn block_with_gas_limit(count: u64, gas_limit: u64) -> RecoveredBlock<EthBlock> {
+        let (_, sender_key_pair, _) = funded_sender();
+        let block = transfer_block_from(&chain_spec(), sender_key_pair, 1, 0..count);
+        let senders = block.senders().to_vec();
+        let mut block = block.into_block();
+        block.header.gas_limit = gas_limit;
+        RecoveredBlock::new_unhashed(block, senders)
+    }
+
+    #[test]
+    fn valid_blocks_pass() {
+        assert_eq!(precheck_block(&block_with_gas_limit(0, 0)), Ok(()));
+        assert_eq!(precheck_block(&block_with_gas_limit(3, 1_000_000)), Ok(()));
+    }
+
+    #[test]
+    fn gas_over_limit_is_rejected() {
+        assert_eq!(
+            precheck_block(&block_with_gas_limit(2, 20_000)),
+            Err(PrecheckError::TransactionGasExceedsLimit {
+                tx_index: 0,
+                gas_limit: 21_000,
+                block_gas_limit: 20_000,
+            })
+        );
+
+        let mut block = block_with_gas_limit(1, 30_000).into_block();
+        block.header.gas_used = 40_000;
+        assert_eq!(
+            precheck_block(&RecoveredBlock::new_unhashed(block, vec![Default::default()])),
+            Err(PrecheckError::GasUsedExceedsLimit { gas_used: 40_000, gas_limit: 30_000 })
+        );
+
+        assert_eq!(
+            precheck_block(&block_with_gas_limit(2, 30_000)),
+            Err(PrecheckError::ExceedsGasCapacity { count: 2, max: 1 })
+        );
+    }
+
+    #[test]
+    fn missing_senders_are_rejected() {
+        let block = block_with_gas_limit(2, 1_000_000).into_block();
+
+        assert_eq!(
+            precheck_block(&RecoveredBlock::new_unhashed(block, Vec::new())),
+            Err(PrecheckError::MissingSenders { transactions: 2, senders: 0 })
+        );
+    }
+
+    #[test]
+    fn cancun_block_without_beacon_root_is_rejected() {
+        let mut header = block_with_gas_limit(1, 1_000_000).header().clone();
+        assert_eq!(header.parent_beacon_block_root, None);
+
+        // Shanghai blocks have no root to check
+        assert!(precheck_beacon_root(SpecId::SHANGHAI, &header).is_ok());
+        assert!(matches!(
+            precheck_beacon_root(SpecId::CANCUN, &header),
+            Err(BlockExecutionError::Validation(
+                BlockValidationError::MissingParentBeaconBlockRoot
+            ))
+        ));
+
+        header.parent_beacon_block_root = Some(B256::ZERO);
+        assert!(precheck_beacon_root(SpecId::CANCUN, &header).is_ok());
+    }
+}
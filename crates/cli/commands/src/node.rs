@@ -165,7 +165,9 @@ impl<
             engine,
         } = self;
         let is_prewarm = engine.clone().caching_and_prewarming_enabled;
-        profiler::TraceMonitor::default().start(is_prewarm);
+        let mut trace_monitor = profiler::TraceMonitor::default();
+        trace_monitor.start(is_prewarm);
+        trace_monitor.install();
         // set up node config
         let mut node_config = NodeConfig {
             datadir,
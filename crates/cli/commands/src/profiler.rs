@@ -1,3 +1,5 @@
+use clap::Parser;
+use eyre::WrapErr;
 use reth_node_core::version;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -5,28 +7,121 @@ use std::time::{SystemTime, UNIX_EPOCH};
 use std::{
     default, fs,
     io::{self, Write},
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::Arc,
 };
 use sysinfo::System;
 use tokio::fs::File;
-use tokio::io::AsyncWriteExt;
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
 use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
 use tokio::time::{sleep, Duration};
 use tracing_chrome::ChromeLayerBuilder;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 #[derive(Serialize, Deserialize)]
 pub struct TraceMonitor {
+    /// Directory receiving the per-block trace files. Relative paths are resolved against the
+    /// working directory.
     out_dir: PathBuf,
+    /// Whether `out_dir` is wiped when the profiler starts. Off by default so previous runs
+    /// are kept.
+    clean_out_dir: bool,
     ssa_enabled: bool,
     parallel_enabled: bool,
     prewarm_enabled: bool,
     cli_version: String,
     timestamp: String,
     is_release: bool,
+    cpu_cores: usize,
+    total_mem_mb: u64,
     hardware: String,
+    /// Optional path of a single Perfetto-loadable file that collects every block's events, in
+    /// addition to the per-block files.
+    combined_output: Option<PathBuf>,
+    /// Inclusive range of block numbers to trace. Blocks outside of it are dropped as soon as
+    /// their start event is seen.
+    block_range: Option<(u64, u64)>,
     #[serde(skip)]
     chrome_guard: Arc<tokio::sync::Mutex<Option<tracing_chrome::FlushGuard>>>,
+    #[serde(skip)]
+    writer_task: Option<JoinHandle<()>>,
+    #[serde(skip)]
+    flush_task: Option<JoinHandle<()>>,
+}
+
+/// The monitor started by the node command, kept so it can be shut down on exit.
+static ACTIVE: std::sync::Mutex<Option<TraceMonitor>> = std::sync::Mutex::new(None);
+
+/// Shuts down the monitor registered with [`TraceMonitor::install`], if any.
+///
+/// Call this before the runtime stops so the trace of the last block is written out.
+pub async fn shutdown_active() {
+    let monitor = ACTIVE.lock().unwrap_or_else(|e| e.into_inner()).take();
+    if let Some(monitor) = monitor {
+        monitor.shutdown().await;
+    }
+}
+
+/// Structured configuration and hardware of a profiling run.
+///
+/// The same values are embedded in every block trace as part of the free-form `hardware`
+/// string; this gives tools a typed view to correlate runs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunMetadata {
+    /// Number of logical CPU cores.
+    pub cpu_cores: usize,
+    /// Total memory in MB.
+    pub total_mem_mb: u64,
+    /// Whether SSA execution is enabled (`ENABLE_SSA`).
+    pub ssa_enabled: bool,
+    /// Whether parallel execution is enabled (`ENABLE_PARALLEL`).
+    pub parallel_enabled: bool,
+    /// Whether prewarming is enabled.
+    pub prewarm_enabled: bool,
+    /// Whether the binary is a release build.
+    pub is_release: bool,
+}
+
+/// Name of the index file written into the output directory.
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// Index of the block files written by a profiling run, kept in `manifest.json` in the output
+/// directory and rewritten after every block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunManifest {
+    /// Configuration and hardware of the run.
+    pub metadata: RunMetadata,
+    /// One entry per block file, in the order the blocks completed.
+    pub blocks: Vec<ManifestEntry>,
+}
+
+/// A block file listed in a [`RunManifest`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// File name, relative to the output directory.
+    pub file: String,
+    /// Block number, `None` for `block_unknown.json`.
+    pub block_num: Option<u64>,
+    /// Number of trace events in the file, not counting the leading run metadata record.
+    pub event_count: usize,
+}
+
+impl RunManifest {
+    /// Adds the entry, replacing an earlier one for the same file.
+    fn record(&mut self, entry: ManifestEntry) {
+        match self.blocks.iter_mut().find(|existing| existing.file == entry.file) {
+            Some(existing) => *existing = entry,
+            None => self.blocks.push(entry),
+        }
+    }
+
+    /// Writes the manifest to a temporary file and renames it into place, so an interrupted
+    /// run always leaves a complete manifest behind.
+    async fn write(&self, out_dir: &Path) -> io::Result<()> {
+        let tmp = out_dir.join(format!("{}.tmp", MANIFEST_FILE));
+        tokio::fs::write(&tmp, serde_json::to_vec_pretty(self)?).await?;
+        tokio::fs::rename(&tmp, out_dir.join(MANIFEST_FILE)).await
+    }
 }
 
 struct BlockData {
@@ -34,9 +129,46 @@ struct BlockData {
     data: Vec<String>,
 }
 
+/// A JSON array file that stays syntactically valid after every append.
+///
+/// The closing bracket is rewritten after each block, so an interrupted run still leaves a
+/// loadable trace behind.
+struct CombinedTraceFile {
+    file: File,
+    // Offset of the trailing "\n]"
+    end: u64,
+    empty: bool,
+}
+
+impl CombinedTraceFile {
+    async fn create(path: &Path) -> io::Result<Self> {
+        let mut file = File::create(path).await?;
+        file.write_all(b"[\n]").await?;
+        file.flush().await?;
+        Ok(Self { file, end: 1, empty: true })
+    }
+
+    async fn append(&mut self, events: &[String]) -> io::Result<()> {
+        if events.is_empty() {
+            return Ok(());
+        }
+        let separator = if self.empty { "\n" } else { ",\n" };
+        let chunk = format!("{}{}", separator, events.join(",\n"));
+
+        self.file.seek(io::SeekFrom::Start(self.end)).await?;
+        self.file.write_all(chunk.as_bytes()).await?;
+        self.end += chunk.len() as u64;
+        self.file.write_all(b"\n]").await?;
+        self.file.flush().await?;
+        self.empty = false;
+        Ok(())
+    }
+}
+
 struct TracingWriter {
     sender: mpsc::Sender<BlockData>,
     system_info: String,
+    block_range: Option<(u64, u64)>,
     buffer: Vec<String>,
     inside_block: bool,
     current_block: Option<String>,
@@ -44,10 +176,15 @@ struct TracingWriter {
 }
 
 impl TracingWriter {
-    fn new(sender: mpsc::Sender<BlockData>, system_info: String) -> Self {
+    fn new(
+        sender: mpsc::Sender<BlockData>,
+        system_info: String,
+        block_range: Option<(u64, u64)>,
+    ) -> Self {
         Self {
             sender,
             system_info,
+            block_range,
             buffer: Vec::new(),
             inside_block: false,
             current_block: None,
@@ -62,6 +199,21 @@ impl TracingWriter {
             let ph = map.get("ph").and_then(|v| v.as_str()).unwrap_or("");
 
             if cat == "block_profiler" && ph == "B" {
+                let block_num =
+                    map.get("args").and_then(|v| v.get("block_num")).and_then(|v| v.as_str());
+
+                // Skip the whole block, its events are ignored until the next block starts
+                if let Some((start, end)) = self.block_range {
+                    let in_range = block_num
+                        .and_then(|num| num.parse::<u64>().ok())
+                        .is_some_and(|num| (start..=end).contains(&num));
+                    if !in_range {
+                        self.inside_block = false;
+                        self.current_block = None;
+                        return;
+                    }
+                }
+
                 let now =
                     SystemTime::now().duration_since(UNIX_EPOCH).expect("Time went backwards");
                 let millis = now.as_millis();
@@ -72,9 +224,7 @@ impl TracingWriter {
                 self.buffer.push(serde_json::to_string(&system).unwrap());
                 self.buffer.push(line_str);
 
-                if let Some(block_num) =
-                    map.get("args").and_then(|v| v.get("block_num")).and_then(|v| v.as_str())
-                {
+                if let Some(block_num) = block_num {
                     self.current_block = Some(block_num.to_string());
                 }
                 return;
@@ -167,7 +317,7 @@ impl TraceMonitor {
 
         let (sender, receiver) = mpsc::channel(100);
         let system_info = serde_json::to_string(&self).unwrap_or_default();
-        let writer = TracingWriter::new(sender, system_info);
+        let writer = TracingWriter::new(sender, system_info, self.block_range);
 
         let (chrome_layer, guard) =
             ChromeLayerBuilder::new().writer(writer).include_args(true).build();
@@ -183,46 +333,141 @@ impl TraceMonitor {
         self.run(receiver);
     }
 
-    fn run(&self, mut receiver: mpsc::Receiver<BlockData>) {
-        if !self.is_enabled() {
-            return;
-        }
+    fn run(&mut self, mut receiver: mpsc::Receiver<BlockData>) {
         let out_dir = self.out_dir.clone();
-        if out_dir.exists() {
+        if self.clean_out_dir && out_dir.exists() {
             let _ = fs::remove_dir_all(&out_dir);
         }
         let _ = fs::create_dir_all(&out_dir);
-        tokio::spawn(async move {
+        let combined_output = self.combined_output.clone();
+        let mut manifest = RunManifest { metadata: self.metadata(), blocks: Vec::new() };
+        self.writer_task = Some(tokio::spawn(async move {
+            let mut combined = match &combined_output {
+                Some(path) => match CombinedTraceFile::create(path).await {
+                    Ok(file) => Some(file),
+                    Err(e) => {
+                        eprintln!("Failed to create combined trace file {:?}: {:?}", path, e);
+                        None
+                    }
+                },
+                None => None,
+            };
             while let Some(block_data) = receiver.recv().await {
+                if let Some(file) = combined.as_mut() {
+                    if let Err(e) = file.append(&block_data.data).await {
+                        eprintln!("Failed to write combined trace file: {:?}", e);
+                    }
+                }
                 let filename = match &block_data.block_num {
                     Some(num) => format!("block_{}.json", num),
                     None => "block_unknown.json".to_string(),
                 };
                 let filepath: PathBuf = out_dir.join(&filename);
+                let mut written = false;
                 if let Ok(mut out) = File::create(&filepath).await {
                     let json_array = format!("[\n{}\n]", block_data.data.join(",\n"));
-                    if let Err(e) = out.write_all(json_array.as_bytes()).await {
-                        eprintln!("Failed to write block file: {:?}", e);
+                    // Flush so the file is complete before the manifest lists it
+                    match out.write_all(json_array.as_bytes()).await {
+                        Ok(()) => written = out.flush().await.is_ok(),
+                        Err(e) => eprintln!("Failed to write block file: {:?}", e),
                     }
                 } else {
                     eprintln!("Failed to create block file: {:?}", filepath);
                 }
+
+                if written {
+                    manifest.record(ManifestEntry {
+                        file: filename,
+                        block_num: block_data.block_num.as_deref().and_then(|num| num.parse().ok()),
+                        event_count: block_data.data.len().saturating_sub(1),
+                    });
+                    if let Err(e) = manifest.write(&out_dir).await {
+                        eprintln!("Failed to write manifest: {:?}", e);
+                    }
+                }
             }
-        });
+        }));
 
-        // Chrome flush
+        // Chrome flush, until the guard is taken by `shutdown`
         let chrome_guard = Arc::clone(&self.chrome_guard);
-        tokio::spawn(async move {
+        self.flush_task = Some(tokio::spawn(async move {
             loop {
                 {
                     let guard_opt = chrome_guard.lock().await;
-                    if let Some(guard) = guard_opt.as_ref() {
-                        let _ = guard.flush();
+                    match guard_opt.as_ref() {
+                        Some(guard) => {
+                            let _ = guard.flush();
+                        }
+                        None => break,
                     }
                 }
                 sleep(Duration::from_millis(500)).await;
             }
-        });
+        }));
+    }
+
+    /// Stops the profiler and writes out everything still buffered.
+    ///
+    /// Dropping the chrome guard makes the chrome layer write its remaining events and flush
+    /// the writer, which hands the last block over to the writer task and closes the channel.
+    /// This then waits for the flush loop to stop and for the writer task to drain the channel.
+    pub async fn shutdown(mut self) {
+        let guard = self.chrome_guard.lock().await.take();
+        if let Some(guard) = guard {
+            // Dropping the guard joins the chrome writer thread
+            let _ = tokio::task::spawn_blocking(move || drop(guard)).await;
+        }
+        if let Some(task) = self.flush_task.take() {
+            let _ = task.await;
+        }
+        if let Some(task) = self.writer_task.take() {
+            let _ = task.await;
+        }
+    }
+
+    /// Registers the monitor as the process-wide active one, to be stopped with
+    /// [`shutdown_active`].
+    pub fn install(self) {
+        *ACTIVE.lock().unwrap_or_else(|e| e.into_inner()) = Some(self);
+    }
+
+    /// Returns the run's configuration and hardware as structured data.
+    pub fn metadata(&self) -> RunMetadata {
+        RunMetadata {
+            cpu_cores: self.cpu_cores,
+            total_mem_mb: self.total_mem_mb,
+            ssa_enabled: self.ssa_enabled,
+            parallel_enabled: self.parallel_enabled,
+            prewarm_enabled: self.prewarm_enabled,
+            is_release: self.is_release,
+        }
+    }
+
+    /// Writes the per-block trace files into `path` instead of `block_perfetto`.
+    ///
+    /// Relative paths are resolved against the working directory.
+    pub fn with_out_dir(mut self, path: impl Into<PathBuf>) -> Self {
+        self.out_dir = path.into();
+        self
+    }
+
+    /// Wipes the output directory when the profiler starts. Existing files are kept otherwise,
+    /// and block files of the new run overwrite those with the same block number.
+    pub fn with_clean_out_dir(mut self, clean: bool) -> Self {
+        self.clean_out_dir = clean;
+        self
+    }
+
+    /// Only traces blocks with numbers in `start..=end`.
+    pub fn with_block_range(mut self, start: u64, end: u64) -> Self {
+        self.block_range = Some((start, end));
+        self
+    }
+
+    /// Also writes every block's events into a single JSON array file at `path`.
+    pub fn with_combined_output(mut self, path: impl Into<PathBuf>) -> Self {
+        self.combined_output = Some(path.into());
+        self
     }
 
     fn is_enabled(&self) -> bool {
@@ -230,6 +475,12 @@ impl TraceMonitor {
     }
 }
 
+/// Parses an inclusive `start-end` block range, e.g. `ALTIUS_TRACE_BLOCKS=100-200`.
+fn parse_block_range(value: &str) -> Option<(u64, u64)> {
+    let (start, end) = value.split_once('-')?;
+    Some((start.trim().parse().ok()?, end.trim().parse().ok()?))
+}
+
 fn env_flag(name: &str) -> bool {
     std::env::var(name).map(|v| v.eq_ignore_ascii_case("true") || v == "1").unwrap_or(false)
 }
@@ -240,27 +491,356 @@ impl default::Default for TraceMonitor {
         let mut sys = System::new_all();
         sys.refresh_all();
 
-        let total_memory = sys.total_memory();
+        let total_mem_mb = sys.total_memory() / 1024 / 1024;
         let cpus = sys.cpus();
         let cpu_brand = cpus.first().map(|c| c.brand()).unwrap_or("unknown");
         Self {
-            out_dir: PathBuf::from("block_perfetto"),
+            out_dir: std::env::var_os("ALTIUS_TRACE_DIR")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| PathBuf::from("block_perfetto")),
+            clean_out_dir: env_flag("ALTIUS_TRACE_CLEAN"),
             ssa_enabled: env_flag("ENABLE_SSA"),
             parallel_enabled: env_flag("ENABLE_PARALLEL"),
             prewarm_enabled: false,
             cli_version: serde_json::to_string(&version).unwrap_or_default(),
             is_release: cfg!(not(debug_assertions)),
+            cpu_cores: cpus.len(),
+            total_mem_mb,
             hardware: format!(
                 "os={} arch={} family={} cpu_cores={} total_mem={}MB cpu_brand={}",
                 std::env::consts::OS,
                 std::env::consts::ARCH,
                 std::env::consts::FAMILY,
                 cpus.len(),
-                total_memory / 1024 / 1024,
+                total_mem_mb,
                 cpu_brand
             ),
+            combined_output: std::env::var_os("ALTIUS_TRACE_COMBINED").map(PathBuf::from),
+            block_range: std::env::var("ALTIUS_TRACE_BLOCKS")
+                .ok()
+                .and_then(|v| parse_block_range(&v)),
             chrome_guard: Arc::new(tokio::sync::Mutex::new(None)),
+            writer_task: None,
+            flush_task: None,
             timestamp: "".to_string(),
         }
     }
 }
+
+/// Block execution times of a profiling run, computed by [`summarize_run`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RunSummary {
+    /// Number of blocks with a complete `block_profiler` span.
+    pub blocks: usize,
+    /// Block files that could not be read or have no complete span.
+    pub skipped: Vec<PathBuf>,
+    /// Shortest block execution time.
+    pub min: Duration,
+    /// Median block execution time.
+    pub median: Duration,
+    /// 95th percentile block execution time.
+    pub p95: Duration,
+    /// Longest block execution time.
+    pub max: Duration,
+}
+
+/// Reads the `block_*.json` files in `dir` and summarizes their block execution times.
+///
+/// The execution time of a block is the distance between the `B` and `E` events of its
+/// `block_profiler` span. Files that are missing, malformed or lack either event are listed in
+/// [`RunSummary::skipped`] instead of failing the whole summary.
+///
+/// # Parameters
+///
+/// * `dir` - The output directory of a profiling run
+///
+/// # Returns
+///
+/// The summary, or an error if `dir` itself cannot be read
+pub fn summarize_run(dir: &Path) -> io::Result<RunSummary> {
+    let mut summary = RunSummary::default();
+    let mut durations = Vec::new();
+
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let is_block_file = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with("block_") && name.ends_with(".json"));
+        if !is_block_file {
+            continue
+        }
+
+        match block_duration(&path) {
+            Some(duration) => durations.push(duration),
+            None => summary.skipped.push(path),
+        }
+    }
+
+    durations.sort_unstable();
+    summary.skipped.sort_unstable();
+    summary.blocks = durations.len();
+    if let (Some(&min), Some(&max)) = (durations.first(), durations.last()) {
+        summary.min = min;
+        summary.max = max;
+        summary.median = percentile(&durations, 50);
+        summary.p95 = percentile(&durations, 95);
+    }
+
+    Ok(summary)
+}
+
+/// Returns the duration of the `block_profiler` span in a block file.
+fn block_duration(path: &Path) -> Option<Duration> {
+    let events: Vec<Value> = serde_json::from_str(&fs::read_to_string(path).ok()?).ok()?;
+    let timestamp = |ph: &str| {
+        events
+            .iter()
+            .find(|event| event["cat"] == "block_profiler" && event["ph"] == ph)
+            .and_then(|event| event["ts"].as_f64())
+    };
+
+    // Chrome trace timestamps are in microseconds
+    let elapsed = timestamp("E")? - timestamp("B")?;
+    (elapsed >= 0.0).then(|| Duration::from_nanos((elapsed * 1_000.0).round() as u64))
+}
+
+/// Nearest-rank percentile of sorted, non-empty `values`.
+fn percentile(values: &[Duration], percent: usize) -> Duration {
+    let rank = (values.len() * percent).div_ceil(100);
+    values[rank.saturating_sub(1)]
+}
+
+/// `reth profile-summary` command
+#[derive(Debug, Parser)]
+pub struct SummaryCommand {
+    /// The output directory of the profiling run.
+    #[arg(value_name = "DIR", default_value = "block_perfetto")]
+    dir: PathBuf,
+}
+
+impl SummaryCommand {
+    /// Execute `profile-summary` command
+    pub async fn execute(self) -> eyre::Result<()> {
+        let summary = summarize_run(&self.dir)
+            .wrap_err_with(|| format!("Could not read profiling run: {}", self.dir.display()))?;
+
+        println!("Blocks: {}", summary.blocks);
+        if summary.blocks > 0 {
+            println!("Min:    {:?}", summary.min);
+            println!("Median: {:?}", summary.median);
+            println!("P95:    {:?}", summary.p95);
+            println!("Max:    {:?}", summary.max);
+        }
+        for path in &summary.skipped {
+            println!("Skipped: {}", path.display());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn combined_trace_file_is_always_valid_json() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("combined.json");
+
+        let mut combined = CombinedTraceFile::create(&path).await.unwrap();
+        let parsed: Vec<Value> = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert!(parsed.is_empty());
+
+        combined.append(&[r#"{"ph":"B"}"#.to_string(), r#"{"ph":"E"}"#.to_string()]).await.unwrap();
+        combined.append(&[r#"{"ph":"B"}"#.to_string()]).await.unwrap();
+
+        // Never closed explicitly, the file must still parse
+        let parsed: Vec<Value> = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(parsed.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn shutdown_flushes_last_block() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut monitor = TraceMonitor::default().with_out_dir(dir.path().join("trace"));
+
+        let (sender, receiver) = mpsc::channel(100);
+        let writer = TracingWriter::new(sender, serde_json::to_string(&monitor).unwrap(), None);
+        let (chrome_layer, guard) =
+            ChromeLayerBuilder::new().writer(writer).include_args(true).build();
+        monitor.chrome_guard = Arc::new(tokio::sync::Mutex::new(Some(guard)));
+        monitor.run(receiver);
+
+        // The block ends right before shutdown, without a periodic flush in between
+        let subscriber = tracing_subscriber::registry().with(chrome_layer);
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!(target: "block_profiler", "block", block_num = 42u64);
+            let _guard = span.enter();
+        });
+        monitor.shutdown().await;
+
+        let block = fs::read_to_string(dir.path().join("trace").join("block_42.json")).unwrap();
+        let events: Vec<Value> = serde_json::from_str(&block).unwrap();
+        assert_eq!(events.last().unwrap()["ph"], "E");
+    }
+
+    /// Runs `monitor` and feeds it a single block, returning once the block file is written.
+    async fn run_single_block(mut monitor: TraceMonitor, block_num: &str) {
+        let (sender, receiver) = mpsc::channel(1);
+        monitor.run(receiver);
+        let data = vec![r#"{"ph":"B"}"#.to_string(), r#"{"ph":"E"}"#.to_string()];
+        sender.send(BlockData { block_num: Some(block_num.to_string()), data }).await.unwrap();
+        drop(sender);
+        monitor.writer_task.take().unwrap().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn out_dir_is_preserved_unless_cleaning() {
+        let dir = tempfile::tempdir().unwrap();
+        let out_dir = dir.path().join("custom");
+        fs::create_dir_all(&out_dir).unwrap();
+        fs::write(out_dir.join("block_1.json"), "[]").unwrap();
+
+        run_single_block(TraceMonitor::default().with_out_dir(&out_dir), "2").await;
+        assert!(out_dir.join("block_1.json").exists());
+        assert!(out_dir.join("block_2.json").exists());
+        assert!(!PathBuf::from("block_perfetto").join("block_2.json").exists());
+
+        let monitor = TraceMonitor::default().with_out_dir(&out_dir).with_clean_out_dir(true);
+        run_single_block(monitor, "3").await;
+        assert!(!out_dir.join("block_1.json").exists());
+        assert!(!out_dir.join("block_2.json").exists());
+        assert!(out_dir.join("block_3.json").exists());
+    }
+
+    #[tokio::test]
+    async fn block_range_limits_emitted_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut monitor = TraceMonitor::default().with_out_dir(dir.path()).with_block_range(10, 12);
+
+        let (sender, receiver) = mpsc::channel(100);
+        let mut writer = TracingWriter::new(
+            sender,
+            serde_json::to_string(&monitor).unwrap(),
+            monitor.block_range,
+        );
+        monitor.run(receiver);
+
+        for block in 8..=14 {
+            let args = serde_json::json!({ "block_num": block.to_string() });
+            writer.process_json(
+                serde_json::json!({ "cat": "block_profiler", "ph": "B", "args": args }),
+            );
+            writer.process_json(serde_json::json!({ "cat": "evm", "ph": "X" }));
+            writer.process_json(serde_json::json!({ "cat": "block_profiler", "ph": "E" }));
+        }
+        drop(writer);
+        monitor.writer_task.take().unwrap().await.unwrap();
+
+        let mut files: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        files.sort();
+        assert_eq!(files, ["block_10.json", "block_11.json", "block_12.json"]);
+
+        // Only the block's own events are kept
+        let block: Vec<Value> =
+            serde_json::from_str(&fs::read_to_string(dir.path().join("block_11.json")).unwrap())
+                .unwrap();
+        assert_eq!(block.len(), 4);
+        assert_eq!(parse_block_range("10-12"), Some((10, 12)));
+        assert_eq!(parse_block_range("10"), None);
+    }
+
+    #[tokio::test]
+    async fn manifest_lists_emitted_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut monitor = TraceMonitor::default().with_out_dir(dir.path());
+
+        let (sender, receiver) = mpsc::channel(100);
+        monitor.run(receiver);
+        for (block_num, events) in [("5", 3), ("6", 1), ("5", 2)] {
+            let mut data = vec![r#"{"name":"metadata"}"#.to_string()];
+            data.extend((0..events).map(|_| r#"{"ph":"X"}"#.to_string()));
+            sender.send(BlockData { block_num: Some(block_num.to_string()), data }).await.unwrap();
+        }
+        drop(sender);
+        monitor.writer_task.take().unwrap().await.unwrap();
+
+        let manifest: RunManifest =
+            serde_json::from_str(&fs::read_to_string(dir.path().join(MANIFEST_FILE)).unwrap())
+                .unwrap();
+        assert_eq!(manifest.metadata, monitor.metadata());
+
+        // A re-executed block replaces its entry
+        let entries: Vec<_> =
+            manifest.blocks.iter().map(|entry| (entry.block_num, entry.event_count)).collect();
+        assert_eq!(entries, [(Some(5), 2), (Some(6), 1)]);
+
+        for entry in &manifest.blocks {
+            let events: Vec<Value> =
+                serde_json::from_str(&fs::read_to_string(dir.path().join(&entry.file)).unwrap())
+                    .unwrap();
+            assert_eq!(events.len(), entry.event_count + 1);
+        }
+        assert!(!dir.path().join("manifest.json.tmp").exists());
+    }
+
+    #[test]
+    fn summary_of_known_durations() {
+        let dir = tempfile::tempdir().unwrap();
+        for (block_num, micros) in [(1, 400), (2, 100), (3, 300), (4, 200)] {
+            let events = serde_json::json!([
+                { "name": "metadata" },
+                { "cat": "block_profiler", "ph": "B", "ts": 1_000.0 },
+                { "cat": "evm", "ph": "X", "ts": 1_010.0 },
+                { "cat": "block_profiler", "ph": "E", "ts": 1_000.0 + micros as f64 },
+            ]);
+            fs::write(dir.path().join(format!("block_{block_num}.json")), events.to_string())
+                .unwrap();
+        }
+        fs::write(dir.path().join("block_5.json"), "[{").unwrap();
+        fs::write(dir.path().join("block_6.json"), r#"[{"cat":"block_profiler","ph":"B"}]"#)
+            .unwrap();
+        fs::write(dir.path().join(MANIFEST_FILE), "{}").unwrap();
+
+        let summary = summarize_run(dir.path()).unwrap();
+
+        assert_eq!(summary.blocks, 4);
+        assert_eq!(summary.min, Duration::from_micros(100));
+        assert_eq!(summary.median, Duration::from_micros(200));
+        assert_eq!(summary.p95, Duration::from_micros(400));
+        assert_eq!(summary.max, Duration::from_micros(400));
+        assert_eq!(
+            summary.skipped,
+            [dir.path().join("block_5.json"), dir.path().join("block_6.json")]
+        );
+
+        assert!(summarize_run(&dir.path().join("missing")).is_err());
+    }
+
+    #[test]
+    fn metadata_matches_hardware_string() {
+        let monitor = TraceMonitor::default();
+        let metadata = monitor.metadata();
+
+        let field = |name: &str| {
+            monitor
+                .hardware
+                .split(' ')
+                .find_map(|part| part.strip_prefix(name)?.strip_prefix('='))
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(field("cpu_cores"), metadata.cpu_cores.to_string());
+        assert_eq!(field("total_mem"), format!("{}MB", metadata.total_mem_mb));
+        assert_eq!(metadata.ssa_enabled, env_flag("ENABLE_SSA"));
+        assert_eq!(metadata.parallel_enabled, env_flag("ENABLE_PARALLEL"));
+        assert_eq!(metadata.is_release, cfg!(not(debug_assertions)));
+
+        let json = serde_json::to_value(&metadata).unwrap();
+        assert_eq!(json["cpu_cores"], metadata.cpu_cores);
+    }
+}
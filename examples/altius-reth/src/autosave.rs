@@ -0,0 +1,116 @@
+//! Periodic saves of the SSA cache.
+//!
+//! Without them the cache is only written when the node exits, so a crash loses everything
+//! learned since startup. Each save rewrites the whole cache file, `save_cache` has no
+//! incremental mode.
+
+use altius_revm::ssa::global_cache;
+use std::{fmt::Debug, sync::Mutex, time::Duration};
+use tokio::{
+    sync::oneshot,
+    task::JoinHandle,
+    time::{self, MissedTickBehavior},
+};
+use tracing::{info, warn};
+
+/// Serializes writes of the cache file, so a timed save never overlaps the save on exit.
+static SAVE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Saves the SSA cache, waiting for a save in progress to finish first.
+pub(crate) fn save_cache() -> bool {
+    save_with(global_cache::save_cache).is_ok()
+}
+
+fn save_with<E>(save: impl FnOnce() -> Result<(), E>) -> Result<(), E> {
+    let _guard = SAVE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    save()
+}
+
+/// Handle of the task started by [`spawn_autosave`].
+#[derive(Debug)]
+pub(crate) struct AutosaveHandle {
+    stop: oneshot::Sender<()>,
+    task: JoinHandle<()>,
+}
+
+impl AutosaveHandle {
+    /// Stops the task, waiting for a save in progress to finish.
+    pub(crate) async fn stop(self) {
+        let _ = self.stop.send(());
+        let _ = self.task.await;
+    }
+}
+
+/// Spawns a task saving the SSA cache every `interval`, the first save happening after one
+/// interval.
+///
+/// Saves take the same lock as [`save_cache`], so they never write the file concurrently with
+/// the save on exit. Must be called from within a tokio runtime.
+pub(crate) fn spawn_autosave(interval: Duration) -> AutosaveHandle {
+    // The error is rendered on the calling side, it is only logged
+    spawn_autosave_with(interval, || global_cache::save_cache().map_err(|err| format!("{err:?}")))
+}
+
+fn spawn_autosave_with<S, E>(interval: Duration, save: S) -> AutosaveHandle
+where
+    S: FnOnce() -> Result<(), E> + Clone + Send + 'static,
+    E: Debug + Send + 'static,
+{
+    let (stop, mut stopped) = oneshot::channel();
+    let task = tokio::spawn(async move {
+        let mut ticker = time::interval(interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        // The first tick completes immediately
+        ticker.tick().await;
+
+        loop {
+            tokio::select! {
+                _ = &mut stopped => break,
+                _ = ticker.tick() => {
+                    let save = save.clone();
+                    match tokio::task::spawn_blocking(move || save_with(save)).await {
+                        Ok(Ok(())) => info!(target: "reth::cli", "Auto-saved SSA cache"),
+                        Ok(Err(err)) => {
+                            warn!(target: "reth::cli", ?err, "Failed to auto-save SSA cache")
+                        }
+                        Err(err) => warn!(target: "reth::cli", %err, "SSA cache auto-save panicked"),
+                    }
+                }
+            }
+        }
+    });
+
+    AutosaveHandle { stop, task }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    #[tokio::test]
+    async fn file_is_updated_after_interval() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache.bin");
+        let saves = Arc::new(AtomicUsize::new(0));
+        let save = {
+            let (path, saves) = (path.clone(), saves.clone());
+            move || std::fs::write(&path, (saves.fetch_add(1, Ordering::SeqCst) + 1).to_string())
+        };
+
+        let handle = spawn_autosave_with(Duration::from_millis(20), save);
+        assert!(!path.exists());
+
+        time::sleep(Duration::from_millis(100)).await;
+        handle.stop().await;
+        let written: usize = std::fs::read_to_string(&path).unwrap().parse().unwrap();
+        assert!(written >= 1);
+
+        // Nothing is saved once the task is stopped
+        time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(saves.load(Ordering::SeqCst), written);
+    }
+}
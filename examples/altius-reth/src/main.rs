@@ -31,16 +31,18 @@ use reth_trie_db::MerklePatriciaTrie;
 use reth_ethereum_engine_primitives::EthEngineTypes;
 use reth_provider::EthStorage;
 use altius_revm::ssa::global_cache;
+use reth_cli_commands::profiler;
 use tracing::info;  
 
 use alloy_rpc_types_eth as _;
 use reth_ethereum_primitives as _;
 use reth_node_api as _;
-use tokio as _;
 
 use tracing_chrome::ChromeLayerBuilder;
 use tracing_subscriber::prelude::*;
 
+mod autosave;
+
 /// Builds a regular ethereum block executor that uses the custom Altius executor.
 #[derive(Debug, Default, Clone, Copy)]
 #[non_exhaustive]
@@ -143,6 +145,26 @@ where
     }
 }
 
+/// Initializes the SSA cache with `init`.
+///
+/// When `strict` is set a failure is returned, otherwise it is reported and the node runs
+/// without a working cache.
+fn init_cache<T, E: std::fmt::Debug>(
+    strict: bool,
+    init: impl FnOnce() -> Result<T, E>,
+) -> Result<(), String> {
+    match init() {
+        Ok(_) => Ok(()),
+        Err(err) if strict => Err(format!(
+            "failed to initialize the SSA cache: {err:?}. Unset SSA_STRICT to run without it"
+        )),
+        Err(err) => {
+            println!("Failed to initialize the SSA cache, continuing without it: {err:?}");
+            Ok(())
+        }
+    }
+}
+
 fn main() {
     // Configure Chrome tracing，specify the output file  
     // let (chrome_layer, guard) = ChromeLayerBuilder::new()
@@ -167,10 +189,24 @@ fn main() {
     .unwrap_or_else(|_| "false".to_string())
     .parse::<bool>()
     .unwrap_or(false);
+    // Refuse to run with SSA enabled but no working cache
+    let is_strict = std::env::var("SSA_STRICT")
+    .unwrap_or_else(|_| "false".to_string())
+    .parse::<bool>()
+    .unwrap_or(false);
     let use_cache = is_ssa || is_collector;
+    // Periodically save the cache, in addition to the save on exit
+    let autosave_interval = std::env::var("ALTIUS_CACHE_AUTOSAVE_SECS")
+        .ok()
+        .and_then(|secs| secs.parse::<u64>().ok())
+        .filter(|secs| use_cache && *secs > 0)
+        .map(std::time::Duration::from_secs);
 
     if use_cache {
-        let _ = global_cache::init_graph_cache();
+        if let Err(err) = init_cache(is_ssa && is_strict, global_cache::init_graph_cache) {
+            eprintln!("Error: {err}");
+            std::process::exit(1);
+        }
     }
     
 
@@ -193,7 +229,16 @@ fn main() {
             }
 
             info!(target: "reth::cli", "Altius node started successfully");
-            node_exit_future.await
+            let autosave = autosave_interval.map(autosave::spawn_autosave);
+            let result = node_exit_future.await;
+
+            if let Some(autosave) = autosave {
+                autosave.stop().await;
+            }
+
+            // Write out the trace of the last block before the runtime stops
+            profiler::shutdown_active().await;
+            result
         })
     {
         eprintln!("Error: {err:?}");
@@ -202,12 +247,31 @@ fn main() {
     
     // Auto-save SSA cache if enabled
     if use_cache {
-        if let Err(_e) = altius_revm::ssa::global_cache::save_cache(){
-            println!("Failed to save SSA cache");
-        } else {
+        if autosave::save_cache() {
             println!("Auto-saved SSA cache");
+        } else {
+            println!("Failed to save SSA cache");
         }
     }        
 
     println!("Program finished - trace file should be available at: altius_node_trace.json");
-} 
\ No newline at end of file
+} 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strict_mode_rejects_unwritable_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let unwritable = dir.path().join("missing").join("ssa_cache.bin");
+        let init = || std::fs::write(&unwritable, b"");
+
+        let err = init_cache(true, init).unwrap_err();
+        assert!(err.contains("SSA_STRICT"), "{err}");
+
+        // The default keeps running without the cache
+        assert!(init_cache(false, init).is_ok());
+        assert!(init_cache(true, || Ok::<_, std::io::Error>(())).is_ok());
+    }
+}
@@ -3,17 +3,128 @@
 //!
 //! This tool loads the SSA cache and analyzes the distribution of node counts
 //! across all cached SSA graphs.
+//! Entries are analyzed in key order, so repeated runs over the same cache produce the
+//! same output and can be diffed.
 //!
 //! Usage:
-//!     cargo run --release --example analyze_graph_nodes
+//!     cargo run --release --example analyze_graph_nodes -- [--code-hash <hex>] [--format <fmt>] [--top-n <n>]
+//!         [--sample <fraction> [--seed <n>]]
+//!
+//! Options:
+//!     --code-hash <hex> - Only analyze the paths of the contract with this code hash (U256).
+//!                         A contract usually has several cached paths, one per distinct
+//!                         execution path; each is reported on its own line with its path
+//!                         hash and node count, sorted by path hash, and the statistics below
+//!                         are computed over these paths only.
+//!     --format <fmt>    - Export format: `json` (default), `csv` or `both`. The CSV file has
+//!                         one row per graph with `code_hash,path_hash,node_count`.
+//!     --top-n <n>       - Number of outliers to list with their code hash and path hash
+//!                         (default: 10). Each can be passed to `query_graph_nodes`.
+//!     --sample <f>      - Only analyze a fraction `0 < f <= 1` of the entries for a quick
+//!                         estimate. Entries are picked by hashing their PathKey with the seed,
+//!                         so the same seed always selects the same subset. Counts are scaled
+//!                         by `1 / f`; tail statistics (p99, maximum, outliers) are unreliable
+//!                         for small samples.
+//!     --seed <n>        - Seed for `--sample` (default: 0)
 //!
 //! Environment Variables:
 //!     SSA_CACHE_PATH - Path to SSA cache file (default: ./ssa_cache.bin)
 
 use std::collections::HashMap;
 use std::env;
+use altius_revm::ssa::PathKey;
+use revm_primitives::U256;
+
+/// Export format of the analysis results
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    Json,
+    Csv,
+    Both,
+}
+
+impl OutputFormat {
+    fn parse(s: &str) -> Result<Self, String> {
+        match s {
+            "json" => Ok(Self::Json),
+            "csv" => Ok(Self::Csv),
+            "both" => Ok(Self::Both),
+            other => Err(format!("Unknown format '{}', expected json, csv or both", other)),
+        }
+    }
+}
+
+/// Node count of a single cached graph
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct GraphRecord {
+    code_hash: U256,
+    path_hash: u64,
+    node_count: usize,
+}
+
+/// Command line options
+struct Args {
+    /// Restrict the analysis to a single contract
+    code_hash: Option<U256>,
+    /// Export format
+    format: OutputFormat,
+    /// Number of outliers to list
+    top_n: usize,
+    /// Fraction of the entries to analyze
+    sample: Option<f64>,
+    /// Seed selecting the sampled entries
+    seed: u64,
+}
+
+impl Args {
+    fn parse() -> Result<Self, String> {
+        let mut args =
+            Self { code_hash: None, format: OutputFormat::Json, top_n: 10, sample: None, seed: 0 };
+        let mut iter = env::args().skip(1);
+
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--code-hash" => {
+                    let value = iter.next().ok_or("--code-hash requires a value")?;
+                    args.code_hash = Some(parse_u256(&value)?);
+                }
+                "--format" => {
+                    let value = iter.next().ok_or("--format requires a value")?;
+                    args.format = OutputFormat::parse(&value)?;
+                }
+                "--top-n" => {
+                    let value = iter.next().ok_or("--top-n requires a value")?;
+                    args.top_n = value
+                        .parse()
+                        .map_err(|e| format!("Failed to parse --top-n '{}': {}", value, e))?;
+                }
+                "--sample" => {
+                    let value = iter.next().ok_or("--sample requires a value")?;
+                    let fraction: f64 = value
+                        .parse()
+                        .map_err(|e| format!("Failed to parse --sample '{}': {}", value, e))?;
+                    if !(fraction > 0.0 && fraction <= 1.0) {
+                        return Err(format!("--sample must be in (0, 1], got {}", fraction));
+                    }
+                    args.sample = Some(fraction);
+                }
+                "--seed" => {
+                    let value = iter.next().ok_or("--seed requires a value")?;
+                    args.seed = value
+                        .parse()
+                        .map_err(|e| format!("Failed to parse --seed '{}': {}", value, e))?;
+                }
+                other => return Err(format!("Unknown argument: {}", other)),
+            }
+        }
+
+        Ok(args)
+    }
+}
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse()?;
+
     // Set cache path if not already set
     if env::var("SSA_CACHE_PATH").is_err() {
         env::set_var("SSA_CACHE_PATH", "./ssa_cache.bin");
@@ -49,8 +160,16 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         return Ok(());
     }
 
+    if let Some(code_hash) = args.code_hash {
+        println!("Filtering by code hash: {:#x}", code_hash);
+    }
+    if let Some(fraction) = args.sample {
+        println!("Sampling {:.2}% of the entries (seed {})", fraction * 100.0, args.seed);
+    }
+
     // Statistics collectors
     let mut node_counts: Vec<usize> = Vec::new();
+    let mut records: Vec<GraphRecord> = Vec::new();
     let mut distribution: HashMap<usize, usize> = HashMap::new();
     let mut logs_count = 0;
     let mut graphs_count = 0;
@@ -61,15 +180,37 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Progress indicator
     let progress_interval = (total_entries / 10).max(1);
 
+    // The store is a concurrent map whose iteration order changes from run to run. Visit the
+    // entries sorted by key so that the progress output and the exported files are reproducible.
+    let keys = sorted_keys(
+        cache.store().iter().map(|entry| (entry.key().code_hash, entry.key().path_hash)),
+    );
+
     // Iterate over all cache entries
-    for (idx, entry) in cache.store().iter().enumerate() {
+    for (idx, (code_hash, path_hash)) in keys.into_iter().enumerate() {
         if idx % progress_interval == 0 {
             println!("  Progress: {}/{} ({:.1}%)",
                      idx, total_entries,
                      (idx as f64 / total_entries as f64) * 100.0);
         }
 
-        let (path_key, artifacts) = (entry.key(), entry.value());
+        let path_key = &PathKey { code_hash, path_hash };
+
+        // Compare the key before touching the value so non-matching logs are never converted
+        if args.code_hash.is_some_and(|code_hash| path_key.code_hash != code_hash) {
+            continue;
+        }
+        if args.sample.is_some_and(|fraction| {
+            !is_sampled(args.seed, fraction, &path_key.code_hash, path_key.path_hash)
+        }) {
+            continue;
+        }
+
+        let Some(entry) = cache.store().get(path_key) else {
+            // Removed since the keys were collected
+            continue;
+        };
+        let artifacts = entry.value();
 
         match &artifacts.data {
             altius_revm::ssa::SsaData::Graph(graph) => {
@@ -78,6 +219,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 let node_count = graph.nodes.len();
                 node_counts.push(node_count);
                 *distribution.entry(node_count).or_insert(0) += 1;
+                records.push(GraphRecord {
+                    code_hash: path_key.code_hash,
+                    path_hash: path_key.path_hash,
+                    node_count,
+                });
             }
             altius_revm::ssa::SsaData::Logs(_) => {
                 // Need to convert logs to graph
@@ -91,6 +237,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                             let node_count = graph.nodes.len();
                             node_counts.push(node_count);
                             *distribution.entry(node_count).or_insert(0) += 1;
+                            records.push(GraphRecord {
+                                code_hash: path_key.code_hash,
+                                path_hash: path_key.path_hash,
+                                node_count,
+                            });
                         }
                     }
                     Err(e) => {
@@ -123,6 +274,40 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         return Ok(());
     }
 
+    // Scale factor from the sample to the whole cache
+    let scale = args.sample.map_or(1.0, |fraction| 1.0 / fraction);
+    if let Some(fraction) = args.sample {
+        let sampled = node_counts.len() as f64;
+        // Standard error of the number of selected entries, scaled to the whole cache
+        let std_error = (sampled * (1.0 - fraction)).sqrt() * scale;
+
+        println!("=============================================================");
+        println!("SAMPLING ESTIMATES");
+        println!("=============================================================\n");
+        println!("  Sampled graphs:          {}", node_counts.len());
+        println!("  Estimated total graphs:  {:.0} ± {:.0}", sampled * scale, 2.0 * std_error);
+        println!("\n  ⚠ Counts below are scaled by {:.2}; percentages and percentiles are", scale);
+        println!("    computed on the sample. Ranges and node counts with few sampled graphs,");
+        println!("    the 99th percentile, the maximum and the outliers are not representative.");
+        println!("    Exported files contain the sampled graphs only.\n");
+    }
+
+    if let Some(code_hash) = args.code_hash {
+        println!("=============================================================");
+        println!("PATHS FOR CODE HASH {:#x}", code_hash);
+        println!("=============================================================\n");
+
+        let mut contract_paths: Vec<_> =
+            records.iter().map(|record| (record.path_hash, record.node_count)).collect();
+        contract_paths.sort_unstable();
+        println!("{:<25} {:<15}", "Path Hash", "Node Count");
+        println!("{}", "-".repeat(40));
+        for (path_hash, node_count) in &contract_paths {
+            println!("{:<25} {:<15}", format!("{:#018x}", path_hash), node_count);
+        }
+        println!();
+    }
+
     // Calculate statistics
     node_counts.sort_unstable();
     let min_nodes = *node_counts.first().unwrap();
@@ -184,12 +369,14 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         range_counts.push((label.to_string(), count, percentage));
     }
 
-    println!("{:<15} {:<15} {:<15}", "Range", "Count", "Percentage");
+    let count_header = if args.sample.is_some() { "Est. Count" } else { "Count" };
+    println!("{:<15} {:<15} {:<15}", "Range", count_header, "Percentage");
     println!("{}", "-".repeat(50));
     for (label, count, percentage) in &range_counts {
         if *count > 0 {
             let bar_len = (percentage / 100.0 * 30.0) as usize;
             let bar = "█".repeat(bar_len);
+            let count = (*count as f64 * scale).round() as usize;
             println!("{:<15} {:<15} {:>6.2}% {}", label, count, percentage, bar);
         }
     }
@@ -202,21 +389,21 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut dist_vec: Vec<_> = distribution.iter().collect();
     dist_vec.sort_by(|a, b| b.1.cmp(a.1));
 
-    println!("{:<5} {:<15} {:<15} {:<15}", "#", "Node Count", "Frequency", "Percentage");
+    let freq_header = if args.sample.is_some() { "Est. Freq." } else { "Frequency" };
+    println!("{:<5} {:<15} {:<15} {:<15}", "#", "Node Count", freq_header, "Percentage");
     println!("{}", "-".repeat(55));
 
     for (i, (node_count, freq)) in dist_vec.iter().take(20).enumerate() {
         let percentage = (**freq as f64 / node_counts.len() as f64) * 100.0;
+        let freq = (**freq as f64 * scale).round() as usize;
         println!("{:<5} {:<15} {:<15} {:>6.2}%", i + 1, node_count, freq, percentage);
     }
 
     // Find outliers (graphs with unusually high node counts)
     let outlier_threshold = node_counts[p95_idx];
-    let outliers: Vec<_> = node_counts
-        .iter()
-        .filter(|&&n| n > outlier_threshold)
-        .copied()
-        .collect();
+    let mut outliers: Vec<_> =
+        records.iter().filter(|record| record.node_count > outlier_threshold).collect();
+    sort_outliers(&mut outliers);
 
     if !outliers.is_empty() {
         println!("\n=============================================================");
@@ -225,17 +412,30 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         println!("Count: {} graphs ({:.2}%)",
                  outliers.len(),
                  (outliers.len() as f64 / node_counts.len() as f64) * 100.0);
-        println!("\nSample outlier node counts (showing up to 10):");
-        for (i, count) in outliers.iter().take(10).enumerate() {
-            println!("  {}. {} nodes", i + 1, count);
+        println!("\nLargest outliers (showing up to {}):", args.top_n);
+        println!("{:<5} {:<68} {:<20} {:<10}", "#", "Code Hash", "Path Hash", "Nodes");
+        println!("{}", "-".repeat(105));
+        for (i, record) in outliers.iter().take(args.top_n).enumerate() {
+            println!(
+                "{:<5} {:<68} {:<20} {:<10}",
+                i + 1,
+                format!("{:#x}", record.code_hash),
+                format!("{:#018x}", record.path_hash),
+                record.node_count
+            );
         }
-        if outliers.len() > 10 {
-            println!("  ... and {} more", outliers.len() - 10);
+        if outliers.len() > args.top_n {
+            println!("  ... and {} more", outliers.len() - args.top_n);
         }
     }
 
-    // Export to JSON
-    export_to_json(&node_counts, &distribution, &range_counts)?;
+    // Export results
+    if matches!(args.format, OutputFormat::Json | OutputFormat::Both) {
+        export_to_json(&node_counts, &distribution, &range_counts)?;
+    }
+    if matches!(args.format, OutputFormat::Csv | OutputFormat::Both) {
+        export_to_csv(&records)?;
+    }
 
     println!("\n=============================================================");
     println!("✓ Analysis complete!");
@@ -244,6 +444,13 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// Parse hex string to U256
+fn parse_u256(s: &str) -> Result<U256, String> {
+    let s = s.strip_prefix("0x").unwrap_or(s);
+    U256::from_str_radix(s, 16)
+        .map_err(|e| format!("Failed to parse U256 from '{}': {}", s, e))
+}
+
 fn export_to_json(
     node_counts: &[usize],
     distribution: &HashMap<usize, usize>,
@@ -300,3 +507,164 @@ fn export_to_json(
 
     Ok(())
 }
+
+/// Decide whether an entry belongs to the sample.
+///
+/// The PathKey is mixed with the seed (splitmix64 finalizer) into a uniform value in `[0, 1)`,
+/// so the selection depends only on the key and the seed, not on the cache iteration order.
+fn is_sampled(seed: u64, fraction: f64, code_hash: &U256, path_hash: u64) -> bool {
+    fn mix(mut z: u64) -> u64 {
+        z = z.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    let hash = code_hash
+        .as_limbs()
+        .iter()
+        .chain(std::iter::once(&path_hash))
+        .fold(mix(seed), |acc, limb| mix(acc ^ limb));
+    // Use the top 53 bits so the value is exactly representable as f64
+    ((hash >> 11) as f64 / (1u64 << 53) as f64) < fraction
+}
+
+/// Collect the `(code_hash, path_hash)` keys of the cache sorted, so entries are visited in the
+/// same order whatever order the cache yields them in.
+fn sorted_keys(keys: impl Iterator<Item = (U256, u64)>) -> Vec<(U256, u64)> {
+    let mut keys: Vec<_> = keys.collect();
+    keys.sort_unstable();
+    keys
+}
+
+/// Sort outliers by descending node count. Ties are ordered by code hash and path hash so
+/// the listing does not depend on the cache iteration order.
+fn sort_outliers(outliers: &mut [&GraphRecord]) {
+    outliers.sort_by(|a, b| {
+        b.node_count
+            .cmp(&a.node_count)
+            .then_with(|| a.code_hash.cmp(&b.code_hash))
+            .then_with(|| a.path_hash.cmp(&b.path_hash))
+    });
+}
+
+fn export_to_csv(records: &[GraphRecord]) -> Result<(), Box<dyn std::error::Error>> {
+    use std::fs::File;
+    use std::io::BufWriter;
+
+    let output_file = "graph_nodes.csv";
+
+    let mut writer = BufWriter::new(File::create(output_file)?);
+    write_csv(records, &mut writer)?;
+
+    println!("\n✓ Per-graph node counts exported to: {}", output_file);
+
+    Ok(())
+}
+
+/// Write one row per graph. Hex fields are quoted so spreadsheets keep them as text,
+/// numeric fields are not.
+fn write_csv<W: std::io::Write>(records: &[GraphRecord], writer: &mut W) -> std::io::Result<()> {
+    writeln!(writer, "code_hash,path_hash,node_count")?;
+    for record in records {
+        writeln!(
+            writer,
+            "\"{:#x}\",\"{:#018x}\",{}",
+            record.code_hash, record.path_hash, record.node_count
+        )?;
+    }
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parse the output of [`write_csv`] back into records.
+    fn read_csv(csv: &str) -> Vec<GraphRecord> {
+        csv.lines()
+            .skip(1)
+            .map(|line| {
+                let fields: Vec<_> = line.split(',').collect();
+                assert_eq!(fields.len(), 3, "unexpected row: {line}");
+                let code_hash = fields[0].strip_prefix('"').unwrap().strip_suffix('"').unwrap();
+                let path_hash = fields[1].strip_prefix('"').unwrap().strip_suffix('"').unwrap();
+                GraphRecord {
+                    code_hash: parse_u256(code_hash).unwrap(),
+                    path_hash: u64::from_str_radix(path_hash.strip_prefix("0x").unwrap(), 16)
+                        .unwrap(),
+                    node_count: fields[2].parse().unwrap(),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn csv_round_trips_node_counts() {
+        let records = vec![
+            GraphRecord { code_hash: U256::from(0xdeadbeefu64), path_hash: 1, node_count: 42 },
+            GraphRecord { code_hash: U256::MAX, path_hash: u64::MAX, node_count: 0 },
+        ];
+
+        let mut out = Vec::new();
+        write_csv(&records, &mut out).unwrap();
+        let csv = String::from_utf8(out).unwrap();
+
+        assert!(csv.starts_with("code_hash,path_hash,node_count\n"));
+        assert!(csv.lines().nth(1).unwrap().ends_with(",42"));
+        assert_eq!(read_csv(&csv), records);
+    }
+
+    #[test]
+    fn sample_matches_requested_fraction() {
+        let keys: Vec<_> =
+            (0..100_000u64).map(|i| (U256::from(i / 10), i.wrapping_mul(0x9e37_79b9))).collect();
+        let sample = |seed, fraction| -> Vec<usize> {
+            (0..keys.len()).filter(|&i| is_sampled(seed, fraction, &keys[i].0, keys[i].1)).collect()
+        };
+
+        for fraction in [0.01, 0.1, 0.5] {
+            let selected = sample(7, fraction).len() as f64;
+            let expected = keys.len() as f64 * fraction;
+            assert!(
+                (selected - expected).abs() <= expected * 0.1,
+                "fraction {fraction}: selected {selected}, expected {expected}"
+            );
+        }
+
+        // Deterministic for a seed, different across seeds
+        assert_eq!(sample(7, 0.1), sample(7, 0.1));
+        assert_ne!(sample(7, 0.1), sample(8, 0.1));
+        assert_eq!(sample(7, 1.0).len(), keys.len());
+    }
+
+    #[test]
+    fn keys_are_visited_in_a_stable_order() {
+        let keys: Vec<_> = (0..1_000u64)
+            .map(|i| (U256::from(i.wrapping_mul(0x9e37_79b9) % 97), i.wrapping_mul(31) % 13))
+            .collect();
+        let mut reversed = keys.clone();
+        reversed.reverse();
+
+        let sorted = sorted_keys(keys.iter().copied());
+        assert!(sorted.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert_eq!(sorted_keys(keys.iter().copied()), sorted);
+        assert_eq!(sorted_keys(reversed.into_iter()), sorted);
+    }
+
+    #[test]
+    fn outliers_are_ordered_deterministically() {
+        let record = |code_hash: u64, path_hash, node_count| GraphRecord {
+            code_hash: U256::from(code_hash),
+            path_hash,
+            node_count,
+        };
+        let records = [record(2, 0, 50), record(1, 7, 50), record(3, 0, 90), record(1, 3, 50)];
+
+        let mut outliers: Vec<_> = records.iter().collect();
+        sort_outliers(&mut outliers);
+
+        let order: Vec<_> = outliers.iter().map(|r| (r.node_count, r.path_hash)).collect();
+        assert_eq!(order, [(90, 0), (50, 3), (50, 7), (50, 0)]);
+    }
+}
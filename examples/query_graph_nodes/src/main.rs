@@ -6,34 +6,79 @@
 //!
 //! Usage:
 //!     cargo run --release --example query_graph_nodes -- <code_hash> <path_hash>
+//!     cargo run --release --example query_graph_nodes -- --export-graph <code_hash> <path_hash> <file>
 //!
 //! Arguments:
 //!     code_hash - Code hash in hex format (U256)
 //!     path_hash - Path hash in hex format (u64)
+//!     file      - With `--export-graph`, the graph is written to this file instead of being
+//!                 printed. The format is detected from the extension: `.json` is supported;
+//!                 `.dot` and the columnar `.bin` format need support in altius-revm and are
+//!                 rejected for now. Logs are converted to a graph before export.
 //!
 //! Environment Variables:
 //!     SSA_CACHE_PATH - Path to SSA cache file (default: ./ssa_cache.bin)
 
 use std::env;
-use altius_revm::ssa::PathKey;
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use altius_revm::ssa::{Graph, PathKey};
 use revm_primitives::U256;
+use serde::Serialize;
+
+/// File formats of `--export-graph`, detected from the file extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExportFormat {
+    /// `.json`: the serde representation of the graph
+    Json,
+    /// `.dot`: Graphviz, needs `Graph::to_dot` from altius-revm
+    Dot,
+    /// `.bin`: the columnar binary format, needs `Graph::to_columnar_bytes` from altius-revm
+    Columnar,
+}
+
+impl ExportFormat {
+    fn from_path(path: &Path) -> Result<Self, String> {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") => Ok(Self::Json),
+            Some("dot") => Ok(Self::Dot),
+            Some("bin") => Ok(Self::Columnar),
+            _ => Err(format!(
+                "Cannot detect the export format of '{}': use a .json, .dot or .bin file",
+                path.display()
+            )),
+        }
+    }
+}
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Parse command line arguments
     let args: Vec<String> = env::args().collect();
 
-    if args.len() != 3 {
-        eprintln!("Usage: {} <code_hash> <path_hash>", args[0]);
-        eprintln!("\nArguments:");
-        eprintln!("  code_hash - Code hash in hex format (U256)");
-        eprintln!("  path_hash - Path hash in hex format (u64)");
-        eprintln!("\nExample:");
-        eprintln!("  {} 0x652b853bbfb85b14c1cfde3a2e36296a7f32dfd18153842a5095184654af2ef 0x347c17d242025249", args[0]);
-        std::process::exit(1);
-    }
+    let (code_hash_str, path_hash_str, export_path) = match args.as_slice() {
+        [_, code_hash, path_hash] => (code_hash, path_hash, None),
+        [_, flag, code_hash, path_hash, file] if flag == "--export-graph" => {
+            (code_hash, path_hash, Some(PathBuf::from(file)))
+        }
+        _ => {
+            eprintln!("Usage: {} <code_hash> <path_hash>", args[0]);
+            eprintln!("       {} --export-graph <code_hash> <path_hash> <file>", args[0]);
+            eprintln!("\nArguments:");
+            eprintln!("  code_hash - Code hash in hex format (U256)");
+            eprintln!("  path_hash - Path hash in hex format (u64)");
+            eprintln!("  file      - Export file, format detected from the extension (.json)");
+            eprintln!("\nExample:");
+            eprintln!("  {} 0x652b853bbfb85b14c1cfde3a2e36296a7f32dfd18153842a5095184654af2ef 0x347c17d242025249", args[0]);
+            std::process::exit(1);
+        }
+    };
 
-    let code_hash_str = &args[1];
-    let path_hash_str = &args[2];
+    // Reject unknown formats before loading the cache, which can take a while
+    let export = export_path
+        .map(|path| ExportFormat::from_path(&path).map(|format| (path, format)))
+        .transpose()?;
 
     // Set cache path if not already set
     if env::var("SSA_CACHE_PATH").is_err() {
@@ -84,45 +129,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Query the cache directly using the path_key
     println!("Querying cache...");
 
-    let store = cache.store();
-    if let Some(entry) = store.get(&path_key) {
-        println!("✓ Found entry!\n");
-
-        let artifacts = entry.value();
-
-        match &artifacts.data {
-            altius_revm::ssa::SsaData::Graph(graph) => {
-                println!("Number of nodes: {}", graph.nodes.len());
-                println!("\n=============================================================");
-                println!("GRAPH NODES");
-                println!("=============================================================\n");
-
-                println!("{:?}", graph.nodes);
-            }
-            altius_revm::ssa::SsaData::Logs(_) => {
-                println!("Graph type: Logs (needs conversion)");
-                println!("Converting logs to graph...");
-
-                let artifacts_clone = artifacts.clone();
-                match artifacts_clone.ensure_graph(cache.as_ref()) {
-                    Ok(converted) => {
-                        if let altius_revm::ssa::SsaData::Graph(graph) = &converted.data {
-                            println!("✓ Conversion successful");
-                            println!("Number of nodes: {}", graph.nodes.len());
-                            println!("\n=============================================================");
-                            println!("GRAPH NODES");
-                            println!("=============================================================\n");
-
-                            println!("{:?}", graph.nodes);
-                        }
-                    }
-                    Err(e) => {
-                        eprintln!("✗ Failed to convert logs to graph: {}", e);
-                        return Err(e.into());
-                    }
-                }
-            }
-        }
+    if let Some(graph) = locate_graph(&path_key)? {
+        output_graph(&graph, export.as_ref())?;
 
         println!("\n=============================================================");
         println!("✓ Query complete!");
@@ -139,6 +147,78 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// Look up the graph of `path_key` in the global cache, converting a `Logs` entry to a graph.
+/// Returns `None` if the cache has no entry for the key.
+fn locate_graph(path_key: &PathKey) -> Result<Option<Graph>, Box<dyn Error>> {
+    let cache = altius_revm::ssa::global_cache::get_cache();
+    let Some(artifacts) = cache.store().get(path_key).map(|entry| entry.value().clone()) else {
+        return Ok(None);
+    };
+    println!("✓ Found entry!\n");
+
+    if let altius_revm::ssa::SsaData::Graph(graph) = &artifacts.data {
+        return Ok(Some(graph.clone()));
+    }
+
+    println!("Graph type: Logs (needs conversion)");
+    println!("Converting logs to graph...");
+    let converted = match artifacts.ensure_graph(cache.as_ref()) {
+        Ok(converted) => converted,
+        Err(e) => {
+            eprintln!("✗ Failed to convert logs to graph: {}", e);
+            return Err(e.into());
+        }
+    };
+    match converted.data {
+        altius_revm::ssa::SsaData::Graph(graph) => {
+            println!("✓ Conversion successful");
+            Ok(Some(graph))
+        }
+        altius_revm::ssa::SsaData::Logs(_) => Err("Conversion did not produce a graph".into()),
+    }
+}
+
+/// Print the nodes of a located graph, or write it to the export file if one was given.
+fn output_graph(
+    graph: &Graph,
+    export: Option<&(PathBuf, ExportFormat)>,
+) -> Result<(), Box<dyn Error>> {
+    println!("Number of nodes: {}", graph.nodes.len());
+
+    if let Some((path, format)) = export {
+        export_graph(graph, path, *format)?;
+        println!("✓ Graph exported to {}", path.display());
+        return Ok(());
+    }
+
+    println!("\n=============================================================");
+    println!("GRAPH NODES");
+    println!("=============================================================\n");
+
+    println!("{:?}", graph.nodes);
+    Ok(())
+}
+
+/// Write `graph` to `path` in `format`. Unsupported formats fail before the file is created.
+fn export_graph<G: Serialize>(
+    graph: &G,
+    path: &Path,
+    format: ExportFormat,
+) -> Result<(), Box<dyn Error>> {
+    match format {
+        ExportFormat::Json => {
+            let mut writer = BufWriter::new(File::create(path)?);
+            serde_json::to_writer_pretty(&mut writer, graph)?;
+            writer.flush()?;
+            Ok(())
+        }
+        ExportFormat::Dot | ExportFormat::Columnar => Err(format!(
+            "{format:?} export needs support in altius-revm (see docs/altius/upstream.md)"
+        )
+        .into()),
+    }
+}
+
 /// Parse hex string to U256
 fn parse_u256(s: &str) -> Result<U256, String> {
     let s = s.strip_prefix("0x").unwrap_or(s);
@@ -152,3 +232,105 @@ fn parse_u64(s: &str) -> Result<u64, String> {
     u64::from_str_radix(s, 16)
         .map_err(|e| format!("Failed to parse u64 from '{}': {}", s, e))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_consensus::{Header, TxLegacy};
+    use alloy_primitives::{bytes, Address, Bytes, TxKind};
+    use reth_chainspec::{ChainSpecBuilder, MAINNET};
+    use reth_ethereum_primitives::{Block, BlockBody, Transaction};
+    use reth_evm::execute::{BlockExecutorProvider, Executor};
+    use reth_evm_altius::{config::AltiusEvmConfig, AltiusBlockExecutorProvider};
+    use reth_primitives_traits::{crypto::secp256k1::public_key_to_address, Block as _};
+    use reth_testing_utils::generators::{self, sign_tx_with_key_pair};
+    use revm::{
+        database::{CacheDB, EmptyDB},
+        state::{AccountInfo, Bytecode},
+    };
+    use std::sync::Arc;
+
+    #[test]
+    fn format_is_detected_from_extension() {
+        assert_eq!(ExportFormat::from_path(Path::new("graph.json")), Ok(ExportFormat::Json));
+        assert_eq!(ExportFormat::from_path(Path::new("out/graph.dot")), Ok(ExportFormat::Dot));
+        assert_eq!(ExportFormat::from_path(Path::new("graph.bin")), Ok(ExportFormat::Columnar));
+        assert!(ExportFormat::from_path(Path::new("graph")).is_err());
+        assert!(ExportFormat::from_path(Path::new("graph.txt")).is_err());
+    }
+
+    /// Executes a block calling a contract that toggles a storage slot, so the executor
+    /// records an SSA entry for it in the global cache.
+    fn execute_contract_call() {
+        let toggle = Address::with_last_byte(0xaa);
+        let chain_spec = Arc::new(ChainSpecBuilder::from(&*MAINNET).shanghai_activated().build());
+        let key_pair = generators::generate_key(&mut generators::rng());
+
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            public_key_to_address(key_pair.public_key()),
+            AccountInfo { balance: U256::from(u64::MAX), ..Default::default() },
+        );
+        // SSTORE(0, ISZERO(SLOAD(0)))
+        let code = Some(Bytecode::new_raw(bytes!("0x6000541560005500")));
+        db.insert_account_info(toggle, AccountInfo { code, ..Default::default() });
+
+        let tx = Transaction::Legacy(TxLegacy {
+            chain_id: Some(chain_spec.chain.id()),
+            nonce: 0,
+            gas_price: 1,
+            gas_limit: 100_000,
+            to: TxKind::Call(toggle),
+            value: Default::default(),
+            input: Bytes::new(),
+        });
+        let header = Header { number: 1, timestamp: 1, gas_limit: 30_000_000, ..Header::default() };
+        let transactions = vec![sign_tx_with_key_pair(key_pair, tx)];
+        let block = Block { header, body: BlockBody { transactions, ..Default::default() } }
+            .try_into_recovered()
+            .unwrap();
+
+        AltiusBlockExecutorProvider::new(AltiusEvmConfig::new(chain_spec))
+            .executor(db)
+            .execute_one(&block)
+            .unwrap();
+    }
+
+    #[test]
+    fn located_graph_exports_and_reloads() {
+        let dir = tempfile::tempdir().unwrap();
+        env::set_var("SSA_CACHE_PATH", dir.path().join("ssa_cache"));
+        env::set_var("ENABLE_SSA", "true");
+        altius_revm::ssa::global_cache::init_graph_cache().unwrap();
+        execute_contract_call();
+
+        let cache = altius_revm::ssa::global_cache::get_cache();
+        let path_key = cache
+            .store()
+            .iter()
+            .map(|entry| PathKey {
+                code_hash: entry.key().code_hash,
+                path_hash: entry.key().path_hash,
+            })
+            .next()
+            .expect("executing a contract call records an SSA entry");
+        // A `Logs` entry is converted, so a graph is located either way
+        let graph = locate_graph(&path_key).unwrap().unwrap();
+        assert!(locate_graph(&PathKey { code_hash: U256::MAX, path_hash: 0 }).unwrap().is_none());
+
+        let path = dir.path().join("graph.json");
+        export_graph(&graph, &path, ExportFormat::from_path(&path).unwrap()).unwrap();
+
+        let reloaded: Graph = serde_json::from_reader(File::open(&path).unwrap()).unwrap();
+        assert_eq!(reloaded.nodes.len(), graph.nodes.len());
+    }
+
+    #[test]
+    fn unsupported_format_creates_no_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("graph.dot");
+
+        assert!(export_graph(&[1u64, 2], &path, ExportFormat::Dot).is_err());
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,93 @@
+#!/usr/bin/env rust
+//! Verify SSA Cache Integrity
+//!
+//! This tool loads the SSA cache and checks that every entry is usable, e.g. after a crash
+//! or an interrupted `save_cache`. Every entry is checked; a bad entry is reported and the
+//! scan continues.
+//!
+//! Checks:
+//!     Logs  - the logs must convert to a graph (`ensure_graph`)
+//!     Graph - not verified; only the node count is visible outside `altius-revm`, so the
+//!             structural checks (dangling node references) need support there, see
+//!             "Cache verification" in docs/altius/upstream.md. Caches holding graphs are
+//!             reported as not fully verified rather than usable.
+//!
+//! Usage:
+//!     cargo run --release --example verify_cache
+//!
+//! Environment Variables:
+//!     SSA_CACHE_PATH - Path to SSA cache file (default: ./ssa_cache.bin)
+//!
+//! Exits with status 1 if the cache cannot be loaded or contains corrupt entries.
+
+use std::env;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Set cache path if not already set
+    if env::var("SSA_CACHE_PATH").is_err() {
+        env::set_var("SSA_CACHE_PATH", "./ssa_cache.bin");
+    }
+
+    println!("=============================================================");
+    println!("SSA Cache Integrity Check");
+    println!("=============================================================\n");
+
+    // Load cache
+    println!("Loading SSA cache from: {}", env::var("SSA_CACHE_PATH")?);
+    if let Err(e) = altius_revm::ssa::global_cache::init_graph_cache() {
+        eprintln!("✗ Failed to load cache: {}", e);
+        std::process::exit(1);
+    }
+    println!("✓ Cache loaded successfully\n");
+
+    let cache = altius_revm::ssa::global_cache::get_cache();
+    let total_entries = cache.len();
+    println!("Total cache entries: {}\n", total_entries);
+
+    let mut graphs_count = 0;
+    let mut logs_count = 0;
+    // (PathKey, error) of every entry that failed a check
+    let mut corrupt: Vec<(String, String)> = Vec::new();
+
+    for entry in cache.store().iter() {
+        let (path_key, artifacts) = (entry.key(), entry.value());
+
+        match &artifacts.data {
+            altius_revm::ssa::SsaData::Graph(_) => {
+                graphs_count += 1;
+            }
+            altius_revm::ssa::SsaData::Logs(_) => {
+                logs_count += 1;
+                if let Err(e) = artifacts.clone().ensure_graph(cache.as_ref()) {
+                    corrupt.push((format!("{:?}", path_key), e.to_string()));
+                }
+            }
+        }
+    }
+
+    println!("=============================================================");
+    println!("SUMMARY");
+    println!("=============================================================\n");
+
+    println!("  Graphs:          {}", graphs_count);
+    println!("  Logs:            {}", logs_count);
+    println!("  Corrupt entries: {}\n", corrupt.len());
+
+    if corrupt.is_empty() {
+        if graphs_count == 0 {
+            println!("✓ Cache is usable");
+        } else {
+            println!("✓ Logs entries are usable");
+            println!("⚠ Graph entries not verified ({} graphs): checking their node", graphs_count);
+            println!("  references needs support from altius-revm");
+        }
+        return Ok(());
+    }
+
+    println!("Corrupt entries:");
+    for (path_key, error) in &corrupt {
+        println!("  {}: {}", path_key, error);
+    }
+
+    std::process::exit(1);
+}